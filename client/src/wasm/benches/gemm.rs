@@ -0,0 +1,33 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use wasm_compute::gemm;
+
+fn input(len: usize) -> Vec<f64> {
+    (0..len).map(|i| (i % 17) as f64 * 0.25).collect()
+}
+
+fn bench_gemm(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gemm");
+    group.sample_size(10);
+
+    for &n in &[64usize, 256, 512] {
+        let a = input(n * n);
+        let b = input(n * n);
+        let a32: Vec<f32> = a.iter().map(|&x| x as f32).collect();
+        let b32: Vec<f32> = b.iter().map(|&x| x as f32).collect();
+
+        group.bench_with_input(BenchmarkId::new("naive_f64", n), &n, |bench, &n| {
+            bench.iter(|| gemm::multiply_naive(black_box(&a), black_box(&b), n, n, n))
+        });
+        group.bench_with_input(BenchmarkId::new("blocked_f64", n), &n, |bench, &n| {
+            bench.iter(|| gemm::multiply(black_box(&a), black_box(&b), n, n, n))
+        });
+        group.bench_with_input(BenchmarkId::new("blocked_f32", n), &n, |bench, &n| {
+            bench.iter(|| gemm::multiply(black_box(&a32), black_box(&b32), n, n, n))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_gemm);
+criterion_main!(benches);
@@ -0,0 +1,166 @@
+//! Stable argsort over one or more columns.
+//!
+//! Instead of sorting an array of row objects, the caller hands over the key
+//! columns and gets back the permutation that sorts them; rows are then
+//! reordered on the JS side with `indices.map(i => rows[i])`.
+
+use std::cmp::Ordering;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, ComputeError, Result};
+use crate::sort::{NanPlacement, SortKey, SortOrder};
+
+#[derive(Debug, Clone)]
+enum Column {
+    Numeric(Vec<f64>, NanPlacement),
+    /// Compared by Unicode code point
+    Text(Vec<String>),
+}
+
+/// Key columns for a multi-key sort, compared in the order they were added
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct SortKeys {
+    len: Option<usize>,
+    keys: Vec<(Column, SortOrder)>,
+}
+
+impl SortKeys {
+    fn push(&mut self, len: usize, column: Column, order: SortOrder) -> Result<()> {
+        match self.len {
+            Some(expected) => check_len("sort key column", len, expected)?,
+            None if len > u32::MAX as usize => {
+                return Err(ComputeError::Overflow("row count exceeds u32 indices"))
+            }
+            None => self.len = Some(len),
+        }
+        self.keys.push((column, order));
+        Ok(())
+    }
+
+    pub fn push_numeric(
+        &mut self,
+        values: Vec<f64>,
+        order: SortOrder,
+        nans: NanPlacement,
+    ) -> Result<()> {
+        self.push(values.len(), Column::Numeric(values, nans), order)
+    }
+
+    pub fn push_text(&mut self, values: Vec<String>, order: SortOrder) -> Result<()> {
+        self.push(values.len(), Column::Text(values), order)
+    }
+
+    fn compare(&self, a: usize, b: usize) -> Ordering {
+        for (column, order) in &self.keys {
+            let ord = match column {
+                Column::Numeric(v, nans) => f64::compare(&v[a], &v[b], *order, *nans),
+                Column::Text(v) => match order {
+                    SortOrder::Ascending => v[a].cmp(&v[b]),
+                    SortOrder::Descending => v[b].cmp(&v[a]),
+                },
+            };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// Stable permutation that sorts the rows by all keys
+    pub fn indices(&self) -> Vec<u32> {
+        let mut idx: Vec<u32> = (0..self.len.unwrap_or(0) as u32).collect();
+        idx.sort_by(|&a, &b| self.compare(a as usize, b as usize));
+        idx
+    }
+}
+
+#[wasm_bindgen]
+impl SortKeys {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SortKeys {
+        SortKeys::default()
+    }
+
+    /// Add a numeric key column (compared after the keys already added)
+    #[wasm_bindgen(js_name = addNumeric)]
+    pub fn add_numeric(
+        &mut self,
+        values: Vec<f64>,
+        order: SortOrder,
+        nans: NanPlacement,
+    ) -> Result<(), JsError> {
+        Ok(self.push_numeric(values, order, nans)?)
+    }
+
+    /// Add a string key column (compared after the keys already added)
+    #[wasm_bindgen(js_name = addStrings)]
+    pub fn add_strings(&mut self, values: Vec<String>, order: SortOrder) -> Result<(), JsError> {
+        Ok(self.push_text(values, order)?)
+    }
+
+    /// Row indices in sorted order; ties keep their original order
+    pub fn argsort(&self) -> Vec<u32> {
+        self.indices()
+    }
+}
+
+/// Stable argsort of a single numeric column
+#[wasm_bindgen]
+pub fn argsort_f64(
+    values: Vec<f64>,
+    order: SortOrder,
+    nans: NanPlacement,
+) -> Result<Vec<u32>, JsError> {
+    let mut keys = SortKeys::new();
+    keys.push_numeric(values, order, nans)?;
+    Ok(keys.indices())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_multi_key_stable() {
+        let mut keys = SortKeys::new();
+        keys.push_text(strings(&["b", "a", "b", "a", "c"]), SortOrder::Ascending)
+            .unwrap();
+        keys.push_numeric(
+            vec![1.0, 2.0, 3.0, 2.0, 0.0],
+            SortOrder::Descending,
+            NanPlacement::Last,
+        )
+        .unwrap();
+        // Rows 1 and 3 tie on both keys and keep their input order
+        assert_eq!(keys.indices(), vec![1, 3, 2, 0, 4]);
+    }
+
+    #[test]
+    fn test_single_column_nans() {
+        let mut keys = SortKeys::new();
+        keys.push_numeric(
+            vec![2.0, f64::NAN, 1.0],
+            SortOrder::Ascending,
+            NanPlacement::First,
+        )
+        .unwrap();
+        assert_eq!(keys.indices(), vec![1, 2, 0]);
+        assert!(SortKeys::new().indices().is_empty());
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let mut keys = SortKeys::new();
+        keys.push_numeric(vec![1.0, 2.0], SortOrder::Ascending, NanPlacement::Last)
+            .unwrap();
+        assert!(keys
+            .push_text(strings(&["x"]), SortOrder::Ascending)
+            .is_err());
+    }
+}
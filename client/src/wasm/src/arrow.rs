@@ -0,0 +1,295 @@
+//! Arrow IPC import and export of [`Table`]s.
+//!
+//! Both the streaming format (`application/vnd.apache.arrow.stream`) and the
+//! file format (`ARROW1` magic and footer) are read and written, so tables
+//! can be exchanged with Arrow JS, DuckDB-wasm or an API serving Arrow.
+//!
+//! Exported column types: booleans as `Boolean`, integers as `Int64`, floats
+//! as `Float64` and text as `Dictionary<Int32, Utf8>`. On import, all integer
+//! widths become integer columns (`UInt64` values above `i64::MAX` make the
+//! column float), all float widths become float columns, and plain, large,
+//! view or dictionary-encoded strings become text. Other Arrow types are
+//! rejected. Compressed IPC buffers are not supported.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float16Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type,
+    UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, DictionaryArray, Float64Array, Int32Array, Int64Array,
+    NullArray, RecordBatch, RecordBatchOptions, StringArray,
+};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ComputeError, Result};
+use crate::table::{Cell, Column, ColumnBuilder, ColumnData, Table};
+
+const FILE_MAGIC: &[u8] = b"ARROW1";
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrowFormat {
+    /// Schema then record batches, for sending over the wire
+    #[default]
+    Stream = 0,
+    /// Random-access file with a footer (`.arrow` / Feather v2)
+    File = 1,
+}
+
+fn arrow_error(e: ArrowError) -> ComputeError {
+    ComputeError::invalid(format!("Arrow IPC: {e}"))
+}
+
+fn to_array(column: &Column) -> Result<ArrayRef> {
+    let valid = column.validity();
+    let options = |i: usize| valid[i].then_some(i);
+    Ok(match column.data() {
+        ColumnData::Null => Arc::new(NullArray::new(column.len())),
+        ColumnData::Boolean(v) => Arc::new(BooleanArray::from_iter(
+            (0..v.len()).map(|i| options(i).map(|i| v[i])),
+        )),
+        ColumnData::Integer(v) => Arc::new(Int64Array::from_iter(
+            (0..v.len()).map(|i| options(i).map(|i| v[i])),
+        )),
+        ColumnData::Float(v) => Arc::new(Float64Array::from_iter(
+            (0..v.len()).map(|i| options(i).map(|i| v[i])),
+        )),
+        ColumnData::Text { codes, dictionary } => {
+            if i32::try_from(dictionary.len()).is_err() {
+                return Err(ComputeError::Overflow("dictionary size exceeds Int32 keys"));
+            }
+            let keys = Int32Array::from_iter(
+                (0..codes.len()).map(|i| options(i).map(|i| codes[i] as i32)),
+            );
+            let values = Arc::new(StringArray::from_iter_values(dictionary));
+            Arc::new(DictionaryArray::try_new(keys, values).map_err(arrow_error)?)
+        }
+    })
+}
+
+/// One record batch holding every column of `table`
+pub fn to_record_batch(table: &Table) -> Result<RecordBatch> {
+    let mut fields = Vec::with_capacity(table.columns().len());
+    let mut arrays = Vec::with_capacity(table.columns().len());
+    for column in table.columns() {
+        let array = to_array(column)?;
+        fields.push(Field::new(column.name(), array.data_type().clone(), true));
+        arrays.push(array);
+    }
+    let options = RecordBatchOptions::new().with_row_count(Some(table.row_count()));
+    RecordBatch::try_new_with_options(Arc::new(Schema::new(fields)), arrays, &options)
+        .map_err(arrow_error)
+}
+
+/// Append the values of `array` to `builder`
+fn push_array(builder: &mut ColumnBuilder, array: &dyn Array) -> Result<()> {
+    macro_rules! push_each {
+        ($values:expr, $cell:expr) => {{
+            let values = $values;
+            for i in 0..array.len() {
+                let cell = if array.is_null(i) {
+                    Cell::Null
+                } else {
+                    $cell(values.value(i))
+                };
+                builder.push(cell)?;
+            }
+        }};
+    }
+    match array.data_type() {
+        DataType::Null => (0..array.len()).try_for_each(|_| builder.push(Cell::Null))?,
+        DataType::Boolean => push_each!(array.as_boolean(), Cell::Boolean),
+        DataType::Int8 => push_each!(array.as_primitive::<Int8Type>(), |x| Cell::Integer(
+            i64::from(x)
+        )),
+        DataType::Int16 => push_each!(array.as_primitive::<Int16Type>(), |x| Cell::Integer(
+            i64::from(x)
+        )),
+        DataType::Int32 => push_each!(array.as_primitive::<Int32Type>(), |x| Cell::Integer(
+            i64::from(x)
+        )),
+        DataType::Int64 => push_each!(array.as_primitive::<Int64Type>(), Cell::Integer),
+        DataType::UInt8 => push_each!(array.as_primitive::<UInt8Type>(), |x| Cell::Integer(
+            i64::from(x)
+        )),
+        DataType::UInt16 => push_each!(array.as_primitive::<UInt16Type>(), |x| Cell::Integer(
+            i64::from(x)
+        )),
+        DataType::UInt32 => push_each!(array.as_primitive::<UInt32Type>(), |x| Cell::Integer(
+            i64::from(x)
+        )),
+        DataType::UInt64 => push_each!(array.as_primitive::<UInt64Type>(), |x: u64| {
+            i64::try_from(x).map_or(Cell::Float(x as f64), Cell::Integer)
+        }),
+        DataType::Float16 => push_each!(array.as_primitive::<Float16Type>(), |x| Cell::Float(
+            f64::from(x)
+        )),
+        DataType::Float32 => push_each!(array.as_primitive::<Float32Type>(), |x| Cell::Float(
+            f64::from(x)
+        )),
+        DataType::Float64 => push_each!(array.as_primitive::<Float64Type>(), Cell::Float),
+        DataType::Utf8 => push_each!(array.as_string::<i32>(), Cell::Text),
+        DataType::LargeUtf8 => push_each!(array.as_string::<i64>(), Cell::Text),
+        DataType::Utf8View => push_each!(array.as_string_view(), Cell::Text),
+        DataType::Dictionary(_, _) => {
+            let dict = array.as_any_dictionary();
+            let mut values = ColumnBuilder::new(String::new());
+            push_array(&mut values, dict.values().as_ref())?;
+            let values = values.finish();
+            for (i, key) in dict.normalized_keys().into_iter().enumerate() {
+                let cell = if array.is_null(i) {
+                    Cell::Null
+                } else {
+                    values.cell(key)
+                };
+                builder.push(cell)?;
+            }
+        }
+        other => {
+            return Err(ComputeError::invalid(format!(
+                "unsupported Arrow type {other} in column {:?}",
+                builder.name()
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Concatenate record batches sharing `schema` into a table
+pub fn from_record_batches<I>(schema: &Schema, batches: I) -> Result<Table>
+where
+    I: IntoIterator<Item = std::result::Result<RecordBatch, ArrowError>>,
+{
+    let mut columns: Vec<ColumnBuilder> = schema
+        .fields()
+        .iter()
+        .map(|f| ColumnBuilder::new(f.name().clone()))
+        .collect();
+    for batch in batches {
+        let batch = batch.map_err(arrow_error)?;
+        for (builder, array) in columns.iter_mut().zip(batch.columns()) {
+            push_array(builder, array.as_ref())?;
+        }
+    }
+    Table::from_columns(columns.into_iter().map(ColumnBuilder::finish).collect())
+}
+
+/// Read an Arrow IPC stream or file, detected from the leading magic bytes
+pub fn read_ipc(bytes: &[u8]) -> Result<Table> {
+    if bytes.starts_with(FILE_MAGIC) {
+        let reader = FileReader::try_new(Cursor::new(bytes), None).map_err(arrow_error)?;
+        let schema = reader.schema();
+        from_record_batches(&schema, reader)
+    } else {
+        let reader = StreamReader::try_new(bytes, None).map_err(arrow_error)?;
+        let schema = reader.schema();
+        from_record_batches(&schema, reader)
+    }
+}
+
+pub fn write_ipc(table: &Table, format: ArrowFormat) -> Result<Vec<u8>> {
+    let batch = to_record_batch(table)?;
+    let mut out = Vec::new();
+    match format {
+        ArrowFormat::Stream => {
+            let mut writer =
+                StreamWriter::try_new(&mut out, &batch.schema()).map_err(arrow_error)?;
+            writer.write(&batch).map_err(arrow_error)?;
+            writer.finish().map_err(arrow_error)?;
+        }
+        ArrowFormat::File => {
+            let mut writer = FileWriter::try_new(&mut out, &batch.schema()).map_err(arrow_error)?;
+            writer.write(&batch).map_err(arrow_error)?;
+            writer.finish().map_err(arrow_error)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Load an Arrow IPC stream or file (e.g. a `fetch` response body) as a `Table`
+#[wasm_bindgen]
+pub fn read_arrow(bytes: &[u8]) -> Result<Table, JsError> {
+    Ok(read_ipc(bytes)?)
+}
+
+/// Serialize `table` as Arrow IPC, readable by `tableFromIPC` in Arrow JS
+#[wasm_bindgen]
+pub fn write_arrow(table: &Table, format: ArrowFormat) -> Result<Vec<u8>, JsError> {
+    Ok(write_ipc(table, format)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::read_json;
+
+    fn sample() -> Table {
+        read_json(
+            br#"[
+                {"id": 1, "name": "a", "score": 1.5, "ok": true, "none": null},
+                {"id": null, "name": "b", "score": null, "ok": false},
+                {"id": 3, "name": "a", "score": -0.25, "ok": null}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let table = sample();
+        for format in [ArrowFormat::Stream, ArrowFormat::File] {
+            let bytes = write_ipc(&table, format).unwrap();
+            assert_eq!(bytes.starts_with(FILE_MAGIC), format == ArrowFormat::File);
+            assert_eq!(read_ipc(&bytes).unwrap(), table);
+        }
+        let empty = Table::default();
+        assert_eq!(
+            read_ipc(&write_ipc(&empty, ArrowFormat::Stream).unwrap()).unwrap(),
+            empty
+        );
+    }
+
+    #[test]
+    fn test_import_types() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("u8", DataType::UInt8, true),
+            Field::new("u64", DataType::UInt64, false),
+            Field::new("s", DataType::LargeUtf8, true),
+        ]));
+        let batch = |u8s: Vec<Option<u8>>, u64s: Vec<u64>, s: Vec<Option<&str>>| {
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(arrow_array::UInt8Array::from(u8s)),
+                Arc::new(arrow_array::UInt64Array::from(u64s)),
+                Arc::new(arrow_array::LargeStringArray::from(s)),
+            ];
+            RecordBatch::try_new(schema.clone(), columns)
+        };
+        let batches = vec![
+            batch(vec![Some(7), None], vec![1, 2], vec![Some("x"), None]),
+            batch(vec![Some(255)], vec![u64::MAX], vec![Some("y")]),
+        ];
+        let table = from_record_batches(&schema, batches).unwrap();
+        let c = table.columns();
+        assert_eq!(c[0].data(), &ColumnData::Integer(vec![7, 0, 255]));
+        assert_eq!(c[0].null_count(), 1);
+        // u64::MAX does not fit an i64, so the column widens to float
+        assert_eq!(
+            c[1].data(),
+            &ColumnData::Float(vec![1.0, 2.0, u64::MAX as f64])
+        );
+        assert_eq!(c[2].text(2).as_deref(), Some("y"));
+
+        let list = arrow_array::ListArray::from_iter_primitive::<Int32Type, _, _>(vec![Some(
+            vec![Some(1)],
+        )]);
+        assert!(push_array(&mut ColumnBuilder::new("l".into()), &list).is_err());
+        assert!(read_ipc(b"not arrow").is_err());
+    }
+}
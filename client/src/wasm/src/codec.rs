@@ -0,0 +1,383 @@
+//! PNG, JPEG, QOI and WebP decoding to RGBA and encoding from RGBA.
+//!
+//! Lets a worker go from `fetch` bytes to [`crate::image`] kernels and back
+//! without `OffscreenCanvas`. Decoded pixels are always 8-bit RGBA with
+//! straight alpha: greyscale is expanded, 16-bit PNG samples are truncated,
+//! palettes and `tRNS` transparency are applied, and CMYK JPEGs are
+//! converted naively (no colour management). Only the first frame of an APNG
+//! or animated WebP is decoded. WebP is always encoded lossless.
+
+use std::io::Cursor;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ComputeError, Result};
+use crate::image::check_image;
+
+/// Largest image the decoders will allocate for (64 megapixels)
+const MAX_PIXELS: usize = 1 << 26;
+
+const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+const JPEG_MAGIC: &[u8] = b"\xff\xd8\xff";
+const QOI_MAGIC: &[u8] = b"qoif";
+const RIFF_MAGIC: &[u8] = b"RIFF";
+const WEBP_MAGIC: &[u8] = b"WEBP";
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png = 0,
+    Jpeg = 1,
+    Qoi = 2,
+    WebP = 3,
+}
+
+impl ImageFormat {
+    /// Format of an encoded image, from its signature bytes
+    pub fn detect(bytes: &[u8]) -> Option<ImageFormat> {
+        if bytes.starts_with(PNG_MAGIC) {
+            Some(ImageFormat::Png)
+        } else if bytes.starts_with(JPEG_MAGIC) {
+            Some(ImageFormat::Jpeg)
+        } else if bytes.starts_with(QOI_MAGIC) {
+            Some(ImageFormat::Qoi)
+        } else if bytes.starts_with(RIFF_MAGIC) && bytes.get(8..12) == Some(WEBP_MAGIC) {
+            Some(ImageFormat::WebP)
+        } else {
+            None
+        }
+    }
+}
+
+/// RGBA pixels of a decoded image
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedImage {
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+    pixels: Vec<u8>,
+}
+
+impl DecodedImage {
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+}
+
+#[wasm_bindgen]
+impl DecodedImage {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Container the pixels were decoded from
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    /// `width × height × 4` bytes, ready for `new ImageData(...)`
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+}
+
+fn codec_error(format: ImageFormat, e: impl std::fmt::Display) -> ComputeError {
+    ComputeError::invalid(format!("{format:?}: {e}"))
+}
+
+fn check_pixels(width: u32, height: u32) -> Result<()> {
+    match (width as usize).checked_mul(height as usize) {
+        Some(n) if n <= MAX_PIXELS => Ok(()),
+        _ => Err(ComputeError::invalid(format!(
+            "{width}×{height} image exceeds the {MAX_PIXELS} pixel limit"
+        ))),
+    }
+}
+
+/// Expand `channels`-byte pixels to RGBA
+fn expand_to_rgba(src: &[u8], channels: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(src.len() / channels * 4);
+    for px in src.chunks_exact(channels) {
+        match *px {
+            [l] => out.extend_from_slice(&[l, l, l, 255]),
+            [l, a] => out.extend_from_slice(&[l, l, l, a]),
+            [r, g, b] => out.extend_from_slice(&[r, g, b, 255]),
+            _ => out.extend_from_slice(px),
+        }
+    }
+    out
+}
+
+fn decode_png(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>)> {
+    let err = |e| codec_error(ImageFormat::Png, e);
+    let mut decoder = png::Decoder::new_with_limits(
+        Cursor::new(bytes),
+        png::Limits {
+            bytes: MAX_PIXELS * 4,
+        },
+    );
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(err)?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    check_pixels(width, height)?;
+    let size = reader
+        .output_buffer_size()
+        .ok_or(ComputeError::Overflow("PNG buffer size"))?;
+    let mut buf = vec![0; size];
+    let frame = reader.next_frame(&mut buf).map_err(err)?;
+    buf.truncate(frame.buffer_size());
+    let channels = frame.color_type.samples();
+    Ok((width, height, expand_to_rgba(&buf, channels)))
+}
+
+fn decode_jpeg(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>)> {
+    let err = |e| codec_error(ImageFormat::Jpeg, e);
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    decoder.set_max_decoding_buffer_size(MAX_PIXELS * 4);
+    decoder.read_info().map_err(err)?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| ComputeError::invalid("Jpeg: missing frame header"))?;
+    let (width, height) = (u32::from(info.width), u32::from(info.height));
+    check_pixels(width, height)?;
+    let buf = decoder.decode().map_err(err)?;
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => expand_to_rgba(&buf, 1),
+        // Big-endian samples: keep the high byte
+        jpeg_decoder::PixelFormat::L16 => {
+            let high: Vec<u8> = buf.chunks_exact(2).map(|s| s[0]).collect();
+            expand_to_rgba(&high, 1)
+        }
+        jpeg_decoder::PixelFormat::RGB24 => expand_to_rgba(&buf, 3),
+        jpeg_decoder::PixelFormat::CMYK32 => {
+            let mut out = Vec::with_capacity(buf.len());
+            for px in buf.chunks_exact(4) {
+                let k = 255 - u32::from(px[3]);
+                let channel = |c: u8| ((255 - u32::from(c)) * k / 255) as u8;
+                out.extend_from_slice(&[channel(px[0]), channel(px[1]), channel(px[2]), 255]);
+            }
+            out
+        }
+    };
+    Ok((width, height, pixels))
+}
+
+fn decode_qoi(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>)> {
+    let err = |e| codec_error(ImageFormat::Qoi, e);
+    let decoder = qoi::Decoder::new(bytes).map_err(err)?;
+    let (width, height) = (decoder.header().width, decoder.header().height);
+    check_pixels(width, height)?;
+    let pixels = decoder
+        .with_channels(qoi::Channels::Rgba)
+        .decode_to_vec()
+        .map_err(err)?;
+    Ok((width, height, pixels))
+}
+
+fn decode_webp(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>)> {
+    let err = |e| codec_error(ImageFormat::WebP, e);
+    let mut decoder = image_webp::WebPDecoder::new(Cursor::new(bytes)).map_err(err)?;
+    decoder.set_memory_limit(MAX_PIXELS * 4);
+    let (width, height) = decoder.dimensions();
+    check_pixels(width, height)?;
+    let size = decoder
+        .output_buffer_size()
+        .ok_or(ComputeError::Overflow("WebP buffer size"))?;
+    let mut buf = vec![0; size];
+    decoder.read_image(&mut buf).map_err(err)?;
+    let channels = if decoder.has_alpha() { 4 } else { 3 };
+    Ok((width, height, expand_to_rgba(&buf, channels)))
+}
+
+/// Decode a PNG, JPEG, QOI or WebP file, detected from its signature, to RGBA
+pub fn decode(bytes: &[u8]) -> Result<DecodedImage> {
+    let format = ImageFormat::detect(bytes)
+        .ok_or_else(|| ComputeError::invalid("unrecognized image format"))?;
+    let (width, height, pixels) = match format {
+        ImageFormat::Png => decode_png(bytes)?,
+        ImageFormat::Jpeg => decode_jpeg(bytes)?,
+        ImageFormat::Qoi => decode_qoi(bytes)?,
+        ImageFormat::WebP => decode_webp(bytes)?,
+    };
+    Ok(DecodedImage {
+        width,
+        height,
+        format,
+        pixels,
+    })
+}
+
+/// RGB copy of `data` when every pixel is opaque, so the alpha channel can be dropped
+fn opaque_rgb(data: &[u8]) -> Option<Vec<u8>> {
+    if !data.chunks_exact(4).all(|px| px[3] == 255) {
+        return None;
+    }
+    Some(
+        data.chunks_exact(4)
+            .flat_map(|px| [px[0], px[1], px[2]])
+            .collect(),
+    )
+}
+
+/// Encode `width × height` RGBA pixels
+///
+/// `quality` (0–100) is the JPEG quality and the PNG compression effort;
+/// QOI and (lossless) WebP ignore it. JPEG has no alpha channel, so alpha is
+/// discarded there. Fully opaque images are written as RGB by the PNG, QOI
+/// and WebP encoders.
+pub fn encode(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+    quality: u8,
+) -> Result<Vec<u8>> {
+    check_image(data, width, height)?;
+    if quality > 100 {
+        return Err(ComputeError::invalid("quality must be in 0..=100"));
+    }
+    let err = |e: &dyn std::fmt::Display| codec_error(format, e);
+    let rgb = opaque_rgb(data);
+    let mut out = Vec::new();
+    match format {
+        ImageFormat::Png => {
+            let mut encoder = png::Encoder::new(&mut out, width, height);
+            encoder.set_color(if rgb.is_some() {
+                png::ColorType::Rgb
+            } else {
+                png::ColorType::Rgba
+            });
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.set_compression(match quality {
+                0..=24 => png::Compression::Fastest,
+                25..=49 => png::Compression::Fast,
+                50..=74 => png::Compression::Balanced,
+                _ => png::Compression::High,
+            });
+            let mut writer = encoder.write_header().map_err(|e| err(&e))?;
+            writer
+                .write_image_data(rgb.as_deref().unwrap_or(data))
+                .map_err(|e| err(&e))?;
+            writer.finish().map_err(|e| err(&e))?;
+        }
+        ImageFormat::Jpeg => {
+            let (w, h) = match (u16::try_from(width), u16::try_from(height)) {
+                (Ok(w), Ok(h)) => (w, h),
+                _ => {
+                    return Err(ComputeError::invalid(
+                        "JPEG dimensions are limited to 65535",
+                    ))
+                }
+            };
+            jpeg_encoder::Encoder::new(&mut out, quality.max(1))
+                .encode(data, w, h, jpeg_encoder::ColorType::Rgba)
+                .map_err(|e| err(&e))?;
+        }
+        ImageFormat::Qoi => {
+            out = qoi::encode_to_vec(rgb.as_deref().unwrap_or(data), width, height)
+                .map_err(|e| err(&e))?;
+        }
+        ImageFormat::WebP => {
+            let (pixels, color) = match &rgb {
+                Some(rgb) => (rgb.as_slice(), image_webp::ColorType::Rgb8),
+                None => (data, image_webp::ColorType::Rgba8),
+            };
+            image_webp::WebPEncoder::new(&mut out)
+                .encode(pixels, width, height, color)
+                .map_err(|e| err(&e))?;
+        }
+    }
+    Ok(out)
+}
+
+/// Decode PNG, JPEG, QOI or WebP bytes (e.g. a `fetch` response) to RGBA pixels
+#[wasm_bindgen]
+pub fn decode_image(bytes: &[u8]) -> Result<DecodedImage, JsError> {
+    Ok(decode(bytes)?)
+}
+
+/// Encode `ImageData` pixels as PNG, JPEG, QOI or WebP; `quality` is 0–100
+#[wasm_bindgen]
+pub fn encode_image(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+    quality: u8,
+) -> Result<Vec<u8>, JsError> {
+    Ok(encode(data, width, height, format, quality)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 5×3 gradient with varying alpha
+    fn sample(alpha: bool) -> Vec<u8> {
+        (0..15u8)
+            .flat_map(|i| [i * 17, 255 - i * 9, i * 5, if alpha { i * 16 } else { 255 }])
+            .collect()
+    }
+
+    #[test]
+    fn test_lossless_round_trip() {
+        for format in [ImageFormat::Png, ImageFormat::Qoi, ImageFormat::WebP] {
+            for alpha in [true, false] {
+                let data = sample(alpha);
+                let bytes = encode(&data, 5, 3, format, 80).unwrap();
+                assert_eq!(ImageFormat::detect(&bytes), Some(format));
+                let image = decode(&bytes).unwrap();
+                assert_eq!((image.width(), image.height()), (5, 3));
+                assert_eq!(image.format(), format);
+                assert_eq!(image.into_pixels(), data, "{format:?} alpha={alpha}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_jpeg_quality() {
+        let data: Vec<u8> = (0..64 * 64)
+            .flat_map(|i: u32| [(i % 64 * 4) as u8, (i / 64 * 4) as u8, 128, 200])
+            .collect();
+        let low = encode(&data, 64, 64, ImageFormat::Jpeg, 10).unwrap();
+        let high = encode(&data, 64, 64, ImageFormat::Jpeg, 95).unwrap();
+        assert!(low.len() < high.len());
+
+        let image = decode(&high).unwrap();
+        assert_eq!((image.width(), image.height()), (64, 64));
+        let max_error = image
+            .pixels()
+            .iter()
+            .zip(&data)
+            .enumerate()
+            .map(|(i, (&a, &b))| if i % 4 == 3 { 255 - a } else { a.abs_diff(b) })
+            .max();
+        // Lossy but close; alpha is dropped to opaque
+        assert!(max_error.unwrap() <= 8, "{max_error:?}");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(decode(b"GIF89a").is_err());
+        assert!(decode(PNG_MAGIC).is_err());
+        assert!(decode(b"RIFF\0\0\0\0WEBPVP8L").is_err());
+        assert_eq!(ImageFormat::detect(b"RIFF\0\0\0\0WAVE"), None);
+        let truncated = encode(&sample(true), 5, 3, ImageFormat::Qoi, 0).unwrap();
+        assert!(decode(&truncated[..truncated.len() / 2]).is_err());
+        assert!(encode(&sample(true), 4, 4, ImageFormat::Png, 50).is_err());
+        assert!(encode(&sample(true), 5, 3, ImageFormat::Jpeg, 101).is_err());
+    }
+}
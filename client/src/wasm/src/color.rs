@@ -0,0 +1,475 @@
+//! Colour space conversions and linear-light adjustments.
+//!
+//! Canvas bytes are sRGB-encoded, so scaling them directly darkens midtones
+//! and shifts hues. The adjustments here decode to linear light, operate
+//! there, and re-encode. HSL and HSV are defined on the encoded sRGB values
+//! (as in CSS); Lab is CIE L\*a\*b\* with a D65 white point.
+//!
+//! Value ranges: linear RGB in `0..=1`; HSL/HSV hue in degrees `0..360` with
+//! the other components in `0..=1`; Lab `L` in `0..=100` and `a`/`b` roughly
+//! `-128..=128`. Alpha is never modified.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, ComputeError, Result};
+use crate::image::{check_rgba, to_u8};
+
+/// Rec. 709 / sRGB luminance weights for linear RGB
+const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+/// D65 reference white in XYZ
+const WHITE: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+/// Mid grey (18% reflectance), the pivot for contrast
+const MID_GREY: f32 = 0.18;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    LinearRgb = 0,
+    Hsl = 1,
+    Hsv = 2,
+    Lab = 3,
+}
+
+/// sRGB transfer function inverse: encoded `0..=1` to linear `0..=1`
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// sRGB transfer function: linear `0..=1` to encoded `0..=1`
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Linear value of every sRGB byte
+fn decode_table() -> [f32; 256] {
+    std::array::from_fn(|i| srgb_to_linear(i as f32 / 255.0))
+}
+
+fn encode(c: f32) -> u8 {
+    to_u8(linear_to_srgb(c.clamp(0.0, 1.0)) * 255.0)
+}
+
+/// Hue in degrees plus (max, min) of an RGB triple
+fn hue(rgb: [f32; 3]) -> (f32, f32, f32) {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let d = max - min;
+    let h = if d == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    (h, max, min)
+}
+
+/// RGB from hue, chroma and the amount `m` added to every channel
+fn from_hue(h: f32, chroma: f32, m: f32) -> [f32; 3] {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let [r, g, b] = match h as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    [r + m, g + m, b + m]
+}
+
+pub fn rgb_to_hsl(rgb: [f32; 3]) -> [f32; 3] {
+    let (h, max, min) = hue(rgb);
+    let l = (max + min) / 2.0;
+    let s = if max == min {
+        0.0
+    } else {
+        (max - min) / (1.0 - (2.0 * l - 1.0).abs())
+    };
+    [h, s, l]
+}
+
+pub fn hsl_to_rgb([h, s, l]: [f32; 3]) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    from_hue(h, chroma, l - chroma / 2.0)
+}
+
+pub fn rgb_to_hsv(rgb: [f32; 3]) -> [f32; 3] {
+    let (h, max, min) = hue(rgb);
+    let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+    [h, s, max]
+}
+
+pub fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let chroma = v * s;
+    from_hue(h, chroma, v - chroma)
+}
+
+/// CIE Lab of a linear sRGB triple
+pub fn linear_to_lab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let xyz = [
+        0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+        0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b,
+        0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b,
+    ];
+    let f = |i: usize| {
+        let t = xyz[i] / WHITE[i];
+        if t > 216.0 / 24_389.0 {
+            t.cbrt()
+        } else {
+            t * 24_389.0 / 27.0 / 116.0 + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(0), f(1), f(2));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Linear sRGB of a CIE Lab triple (may fall outside `0..=1`)
+pub fn lab_to_linear([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let fy = (l + 16.0) / 116.0;
+    let finv = |t: f32| {
+        if t > 6.0 / 29.0 {
+            t * t * t
+        } else {
+            (116.0 * t - 16.0) * 27.0 / 24_389.0
+        }
+    };
+    let x = finv(fy + a / 500.0) * WHITE[0];
+    let y = finv(fy) * WHITE[1];
+    let z = finv(fy - b / 200.0) * WHITE[2];
+    [
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    ]
+}
+
+/// Three components per pixel of `data` in `space`
+pub fn to_space(data: &[u8], space: ColorSpace) -> Result<Vec<f32>> {
+    check_rgba(data)?;
+    let table = decode_table();
+    let mut out = Vec::with_capacity(data.len() / 4 * 3);
+    for px in data.chunks_exact(4) {
+        let srgb = [px[0], px[1], px[2]].map(|c| c as f32 / 255.0);
+        let linear = [px[0], px[1], px[2]].map(|c| table[c as usize]);
+        out.extend_from_slice(&match space {
+            ColorSpace::LinearRgb => linear,
+            ColorSpace::Hsl => rgb_to_hsl(srgb),
+            ColorSpace::Hsv => rgb_to_hsv(srgb),
+            ColorSpace::Lab => linear_to_lab(linear),
+        });
+    }
+    Ok(out)
+}
+
+/// Write `values` (three per pixel, in `space`) into the RGB of `data`
+pub fn from_space(values: &[f32], space: ColorSpace, data: &mut [u8]) -> Result<()> {
+    check_rgba(data)?;
+    check_len("colour values", values.len(), data.len() / 4 * 3)?;
+    for (v, px) in values.chunks_exact(3).zip(data.chunks_exact_mut(4)) {
+        let v = [v[0], v[1], v[2]];
+        let rgb = match space {
+            ColorSpace::LinearRgb => v.map(encode),
+            ColorSpace::Hsl => hsl_to_rgb(v).map(|c| to_u8(c * 255.0)),
+            ColorSpace::Hsv => hsv_to_rgb(v).map(|c| to_u8(c * 255.0)),
+            ColorSpace::Lab => lab_to_linear(v).map(encode),
+        };
+        px[..3].copy_from_slice(&rgb);
+    }
+    Ok(())
+}
+
+/// Apply `f` to every colour channel in linear light, through a byte lookup table
+fn map_channels(data: &mut [u8], f: impl Fn(f32) -> f32) -> Result<()> {
+    check_rgba(data)?;
+    let table = decode_table();
+    let lut: [u8; 256] = std::array::from_fn(|i| encode(f(table[i])));
+    for px in data.chunks_exact_mut(4) {
+        for c in &mut px[..3] {
+            *c = lut[*c as usize];
+        }
+    }
+    Ok(())
+}
+
+/// Apply `f` to every pixel's linear RGB
+fn map_pixels(data: &mut [u8], f: impl Fn([f32; 3]) -> [f32; 3]) -> Result<()> {
+    check_rgba(data)?;
+    let table = decode_table();
+    for px in data.chunks_exact_mut(4) {
+        let rgb = f([px[0], px[1], px[2]].map(|c| table[c as usize]));
+        px[..3].copy_from_slice(&rgb.map(encode));
+    }
+    Ok(())
+}
+
+fn check_factor(name: &str, x: f32) -> Result<()> {
+    if x.is_finite() && x >= 0.0 {
+        Ok(())
+    } else {
+        Err(ComputeError::invalid(format!(
+            "{name} must be finite and non-negative, got {x}"
+        )))
+    }
+}
+
+/// Scale linear intensity by `factor` (like an exposure change)
+pub fn brightness_rgba(data: &mut [u8], factor: f32) -> Result<()> {
+    check_factor("brightness factor", factor)?;
+    map_channels(data, |c| c * factor)
+}
+
+/// Power curve pivoting on mid grey; `amount` 1 is unchanged, 0 is flat grey
+pub fn contrast_rgba(data: &mut [u8], amount: f32) -> Result<()> {
+    check_factor("contrast", amount)?;
+    map_channels(data, |c| MID_GREY * (c / MID_GREY).powf(amount))
+}
+
+/// Push colours away from (or, below 1, towards) their luminance
+pub fn saturation_rgba(data: &mut [u8], factor: f32) -> Result<()> {
+    check_factor("saturation factor", factor)?;
+    map_pixels(data, |rgb| {
+        let y = LUMA[0] * rgb[0] + LUMA[1] * rgb[1] + LUMA[2] * rgb[2];
+        rgb.map(|c| y + (c - y) * factor)
+    })
+}
+
+/// Rotate hues by `degrees` about the grey axis, keeping luminance
+/// (the matrix of CSS `hue-rotate`, applied to linear RGB, with its sine
+/// terms re-derived for the Rec. 709 weights)
+pub fn hue_rotate_rgba(data: &mut [u8], degrees: f32) -> Result<()> {
+    if !degrees.is_finite() {
+        return Err(ComputeError::invalid("hue rotation must be finite"));
+    }
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let [lr, lg, lb] = LUMA;
+    // Green row of the sine part, solved so every column has zero luminance
+    let (sr, sg, sb) = (
+        (lr * lr + lb * (1.0 - lr)) / lg,
+        lr - lb,
+        -(lr * (1.0 - lb) + lb * lb) / lg,
+    );
+    let m = [
+        [
+            lr + cos * (1.0 - lr) - sin * lr,
+            lg - cos * lg - sin * lg,
+            lb - cos * lb + sin * (1.0 - lb),
+        ],
+        [
+            lr - cos * lr + sin * sr,
+            lg + cos * (1.0 - lg) + sin * sg,
+            lb - cos * lb + sin * sb,
+        ],
+        [
+            lr - cos * lr - sin * (1.0 - lr),
+            lg - cos * lg + sin * lg,
+            lb + cos * (1.0 - lb) + sin * lb,
+        ],
+    ];
+    map_pixels(data, |[r, g, b]| {
+        m.map(|row| row[0] * r + row[1] * g + row[2] * b)
+    })
+}
+
+/// `c^(1/gamma)` in linear light; above 1 brightens midtones
+pub fn gamma_rgba(data: &mut [u8], gamma: f32) -> Result<()> {
+    if !(gamma.is_finite() && gamma > 0.0) {
+        return Err(ComputeError::invalid(format!(
+            "gamma must be finite and positive, got {gamma}"
+        )));
+    }
+    map_channels(data, |c| c.powf(gamma.recip()))
+}
+
+/// Levels: map `in_black..=in_white` to `out_black..=out_white` with a
+/// midtone `gamma`. The points are sRGB byte values, as a levels dialog
+/// shows them; the remap itself happens in linear light.
+pub fn levels_rgba(
+    data: &mut [u8],
+    in_black: u8,
+    in_white: u8,
+    gamma: f32,
+    out_black: u8,
+    out_white: u8,
+) -> Result<()> {
+    if in_black >= in_white {
+        return Err(ComputeError::invalid(format!(
+            "input black point {in_black} must be below white point {in_white}"
+        )));
+    }
+    if !(gamma.is_finite() && gamma > 0.0) {
+        return Err(ComputeError::invalid(format!(
+            "gamma must be finite and positive, got {gamma}"
+        )));
+    }
+    let table = decode_table();
+    let [ib, iw, ob, ow] = [in_black, in_white, out_black, out_white].map(|v| table[v as usize]);
+    map_channels(data, |c| {
+        let t = ((c - ib) / (iw - ib)).clamp(0.0, 1.0);
+        ob + t.powf(gamma.recip()) * (ow - ob)
+    })
+}
+
+/// Convert `ImageData` pixels to `ColorSpace` components, three per pixel
+#[wasm_bindgen]
+pub fn to_color_space(data: &[u8], space: ColorSpace) -> Result<Vec<f32>, JsError> {
+    Ok(to_space(data, space)?)
+}
+
+/// Write components from `to_color_space` back into the RGB of `data` (alpha kept)
+#[wasm_bindgen]
+pub fn from_color_space(values: &[f32], space: ColorSpace, data: &mut [u8]) -> Result<(), JsError> {
+    Ok(from_space(values, space, data)?)
+}
+
+/// Contrast around mid grey in linear light (1 = unchanged)
+#[wasm_bindgen]
+pub fn adjust_contrast(data: &mut [u8], amount: f32) -> Result<(), JsError> {
+    Ok(contrast_rgba(data, amount)?)
+}
+
+/// Saturation in linear light (0 = grey, 1 = unchanged)
+#[wasm_bindgen]
+pub fn adjust_saturation(data: &mut [u8], factor: f32) -> Result<(), JsError> {
+    Ok(saturation_rgba(data, factor)?)
+}
+
+/// Luminance-preserving hue rotation by `degrees`
+#[wasm_bindgen]
+pub fn rotate_hue(data: &mut [u8], degrees: f32) -> Result<(), JsError> {
+    Ok(hue_rotate_rgba(data, degrees)?)
+}
+
+/// Gamma in linear light (1 = unchanged)
+#[wasm_bindgen]
+pub fn adjust_gamma(data: &mut [u8], gamma: f32) -> Result<(), JsError> {
+    Ok(gamma_rgba(data, gamma)?)
+}
+
+/// Input/output levels with midtone gamma; points are 0–255 sRGB values
+#[wasm_bindgen]
+pub fn adjust_levels(
+    data: &mut [u8],
+    in_black: u8,
+    in_white: u8,
+    gamma: f32,
+    out_black: u8,
+    out_white: u8,
+) -> Result<(), JsError> {
+    Ok(levels_rgba(
+        data, in_black, in_white, gamma, out_black, out_white,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f32; 3], b: [f32; 3], tol: f32) -> bool {
+        a.iter().zip(&b).all(|(x, y)| (x - y).abs() <= tol)
+    }
+
+    #[test]
+    fn test_conversions() {
+        let red = [1.0, 0.0, 0.0];
+        assert_eq!(rgb_to_hsl(red), [0.0, 1.0, 0.5]);
+        assert_eq!(rgb_to_hsv([0.0, 0.5, 0.5]), [180.0, 1.0, 0.5]);
+        assert!(close(linear_to_lab(red), [53.24, 80.09, 67.20], 0.02));
+        assert!(close(linear_to_lab([1.0; 3]), [100.0, 0.0, 0.0], 0.01));
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+
+        // Every byte triple survives a trip through each space
+        let data: Vec<u8> = (0..=255u8)
+            .flat_map(|i| [i, i.wrapping_mul(37), 255 - i, i])
+            .collect();
+        for space in [
+            ColorSpace::LinearRgb,
+            ColorSpace::Hsl,
+            ColorSpace::Hsv,
+            ColorSpace::Lab,
+        ] {
+            let values = to_space(&data, space).unwrap();
+            let mut back = vec![0; data.len()];
+            back.iter_mut().skip(3).step_by(4).for_each(|a| *a = 7);
+            from_space(&values, space, &mut back).unwrap();
+            for (p, q) in data.chunks(4).zip(back.chunks(4)) {
+                assert_eq!(&p[..3], &q[..3], "{space:?}");
+                assert_eq!(q[3], 7);
+            }
+        }
+        assert!(from_space(&[0.0; 2], ColorSpace::Hsl, &mut [0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_identity_adjustments() {
+        let data: Vec<u8> = (0..=255u8).flat_map(|i| [i, 255 - i, i / 2, 9]).collect();
+        let run = |f: &dyn Fn(&mut [u8]) -> Result<()>| {
+            let mut d = data.clone();
+            f(&mut d).unwrap();
+            d
+        };
+        assert_eq!(run(&|d| brightness_rgba(d, 1.0)), data);
+        assert_eq!(run(&|d| contrast_rgba(d, 1.0)), data);
+        assert_eq!(run(&|d| saturation_rgba(d, 1.0)), data);
+        assert_eq!(run(&|d| gamma_rgba(d, 1.0)), data);
+        assert_eq!(run(&|d| levels_rgba(d, 0, 255, 1.0, 0, 255)), data);
+        let rotated = run(&|d| hue_rotate_rgba(d, 360.0));
+        assert!(rotated.iter().zip(&data).all(|(a, b)| a.abs_diff(*b) <= 1));
+    }
+
+    #[test]
+    fn test_linear_light() {
+        // Doubling light on sRGB 128 gives about 176, not the byte-scaled 255
+        let mut px = [128, 128, 128, 255];
+        brightness_rgba(&mut px, 2.0).unwrap();
+        assert_eq!(px, [176, 176, 176, 255]);
+
+        let mut px = [200, 40, 90, 255];
+        saturation_rgba(&mut px, 0.0).unwrap();
+        assert!(px[0] == px[1] && px[1] == px[2]);
+
+        let mut px = [30, 30, 30, 255, 220, 220, 220, 255];
+        levels_rgba(&mut px, 30, 220, 1.0, 0, 255).unwrap();
+        assert_eq!(px, [0, 0, 0, 255, 255, 255, 255, 255]);
+
+        assert!(brightness_rgba(&mut px, -1.0).is_err());
+        assert!(levels_rgba(&mut px, 9, 9, 1.0, 0, 255).is_err());
+        assert!(gamma_rgba(&mut [0; 3], 2.0).is_err());
+    }
+
+    #[test]
+    fn test_hue_rotate_keeps_luminance() {
+        // Muted colours, so the rotated ones stay inside the gamut
+        let data: Vec<u8> = (0..512u32)
+            .flat_map(|i| {
+                let c = |shift: u32| (100 + (i >> shift & 7) * 8) as u8;
+                [c(0), c(3), c(6), 255]
+            })
+            .collect();
+        let table = decode_table();
+        let y = |px: &[u8]| {
+            let l = LUMA[0] * table[px[0] as usize]
+                + LUMA[1] * table[px[1] as usize]
+                + LUMA[2] * table[px[2] as usize];
+            encode(l)
+        };
+        let mut rotated = data.clone();
+        hue_rotate_rgba(&mut rotated, 120.0).unwrap();
+        assert_ne!(rotated, data);
+        for (a, b) in data.chunks(4).zip(rotated.chunks(4)) {
+            assert!(y(a).abs_diff(y(b)) <= 1, "{a:?} -> {b:?}");
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Incremental CSV reader.
+//!
+//! Bytes are pushed in arbitrary chunks (a chunk may end mid-field, mid-quote
+//! or mid-UTF-8 sequence) and only the current record is buffered, so memory
+//! use follows the size of the resulting columns rather than of the text.
+//!
+//! Quoting follows RFC 4180: fields may be wrapped in double quotes, `""`
+//! inside a quoted field is a literal quote, and quoted fields may contain
+//! delimiters and line breaks. Records end at `\n`, `\r\n` or `\r`; blank
+//! lines are skipped. A stray quote inside an unquoted field is kept as text.
+//!
+//! Column types are inferred from the values: empty fields are null,
+//! `true`/`false` (any case) are booleans, and numbers are integers when they
+//! have no fraction or exponent and fit in an `i64`, floats otherwise.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ComputeError, Result};
+use crate::table::{Cell, ColumnBuilder, Table};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    FieldStart,
+    Unquoted,
+    Quoted,
+    /// Saw a quote inside a quoted field: either an escape or the closing quote
+    QuoteInQuoted,
+}
+
+/// Streaming CSV parser; feed it with `push` and collect the table with `finish`
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct CsvReader {
+    delimiter: u8,
+    has_header: bool,
+    state: State,
+    /// Previous byte was a `\r` ending a record, so a following `\n` is skipped
+    after_cr: bool,
+    prev: u8,
+    field: Vec<u8>,
+    /// Fields of the current record, back to back
+    record: Vec<u8>,
+    ends: Vec<usize>,
+    /// Absolute byte offset of the next byte and of the current line's start
+    offset: usize,
+    line: usize,
+    line_start: usize,
+    /// Line where the current record started
+    record_line: usize,
+    columns: Option<Vec<ColumnBuilder>>,
+}
+
+/// Cell for the text of one field
+fn infer(field: &str) -> Cell<'_> {
+    let t = field.trim_matches(|c: char| c.is_ascii_whitespace());
+    if t.is_empty() {
+        return Cell::Null;
+    }
+    if t.eq_ignore_ascii_case("true") {
+        return Cell::Boolean(true);
+    }
+    if t.eq_ignore_ascii_case("false") {
+        return Cell::Boolean(false);
+    }
+    // Rules out "inf", "NaN" and friends, which `f64::from_str` accepts
+    let numeric =
+        t.bytes().all(|b| b"0123456789+-.eE".contains(&b)) && t.bytes().any(|b| b.is_ascii_digit());
+    if numeric {
+        if !t.contains(['.', 'e', 'E']) {
+            if let Ok(x) = t.parse::<i64>() {
+                return Cell::Integer(x);
+            }
+        }
+        if let Ok(x) = t.parse::<f64>() {
+            return Cell::Float(x);
+        }
+    }
+    Cell::Text(field)
+}
+
+impl CsvReader {
+    pub fn with_options(delimiter: u8, has_header: bool) -> Result<CsvReader> {
+        if matches!(delimiter, b'"' | b'\r' | b'\n') || !delimiter.is_ascii() {
+            return Err(ComputeError::invalid(format!(
+                "delimiter must be an ASCII character other than a quote or line break, got {:?}",
+                delimiter as char
+            )));
+        }
+        Ok(CsvReader {
+            delimiter,
+            has_header,
+            state: State::FieldStart,
+            after_cr: false,
+            prev: 0,
+            field: Vec::new(),
+            record: Vec::new(),
+            ends: Vec::new(),
+            offset: 0,
+            line: 1,
+            line_start: 0,
+            record_line: 1,
+            columns: None,
+        })
+    }
+
+    fn error(&self, line: usize, column: usize, message: impl Into<String>) -> ComputeError {
+        ComputeError::Parse {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn end_field(&mut self) {
+        self.record.extend_from_slice(&self.field);
+        self.ends.push(self.record.len());
+        self.field.clear();
+        self.state = State::FieldStart;
+    }
+
+    fn end_record(&mut self) -> Result<()> {
+        // A line break with nothing before it is a blank line
+        let blank = self.state == State::FieldStart && self.ends.is_empty();
+        if !blank {
+            self.end_field();
+            self.commit()?;
+        }
+        self.record.clear();
+        self.ends.clear();
+        Ok(())
+    }
+
+    /// Hand the buffered record to the column builders
+    fn commit(&mut self) -> Result<()> {
+        let line = self.record_line;
+        let mut fields = Vec::with_capacity(self.ends.len());
+        let mut start = 0;
+        for (i, &end) in self.ends.iter().enumerate() {
+            let field = std::str::from_utf8(&self.record[start..end]).map_err(|e| {
+                self.error(line, 1, format!("field {} is not valid UTF-8: {e}", i + 1))
+            })?;
+            fields.push(field);
+            start = end;
+        }
+        if self.columns.is_none() && line == 1 {
+            if let Some(first) = fields.first_mut() {
+                *first = first.strip_prefix('\u{feff}').unwrap_or(first);
+            }
+        }
+
+        if self.columns.is_none() {
+            let names: Vec<String> = if self.has_header {
+                fields.iter().map(|f| f.to_string()).collect()
+            } else {
+                (1..=fields.len()).map(|i| format!("column_{i}")).collect()
+            };
+            self.columns = Some(names.into_iter().map(ColumnBuilder::new).collect());
+            if self.has_header {
+                return Ok(());
+            }
+        }
+        let columns = self
+            .columns
+            .as_mut()
+            .expect("columns are set by the first record");
+        if fields.len() != columns.len() {
+            return Err(ComputeError::Parse {
+                line,
+                column: 1,
+                message: format!("expected {} fields, found {}", columns.len(), fields.len()),
+            });
+        }
+        for (column, field) in columns.iter_mut().zip(fields) {
+            column.push_field(infer(field), field)?;
+        }
+        Ok(())
+    }
+
+    /// Parse the next chunk of input
+    pub fn push_bytes(&mut self, chunk: &[u8]) -> Result<()> {
+        for &b in chunk {
+            let after_cr = std::mem::take(&mut self.after_cr);
+            let pos = self.offset;
+            self.offset += 1;
+            // `\r\n` is one line break, inside quotes too
+            let prev = std::mem::replace(&mut self.prev, b);
+            let crlf = b == b'\n' && prev == b'\r';
+            if crlf {
+                self.line_start = self.offset;
+            }
+            if crlf && after_cr {
+                continue;
+            }
+            if self.state == State::FieldStart && self.ends.is_empty() {
+                self.record_line = self.line;
+            }
+            let newline = b == b'\n' || b == b'\r';
+            match self.state {
+                State::Quoted if b == b'"' => self.state = State::QuoteInQuoted,
+                State::Quoted => self.field.push(b),
+                State::FieldStart if b == b'"' => self.state = State::Quoted,
+                State::QuoteInQuoted if b == b'"' => {
+                    self.field.push(b'"');
+                    self.state = State::Quoted;
+                }
+                _ if b == self.delimiter => self.end_field(),
+                _ if newline => {
+                    self.end_record()?;
+                    self.after_cr = b == b'\r';
+                }
+                State::QuoteInQuoted => {
+                    return Err(self.error(
+                        self.line,
+                        pos - self.line_start + 1,
+                        format!("unexpected {:?} after closing quote", b as char),
+                    ))
+                }
+                State::FieldStart | State::Unquoted => {
+                    self.field.push(b);
+                    self.state = State::Unquoted;
+                }
+            }
+            if newline && !crlf {
+                self.line += 1;
+                self.line_start = self.offset;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush the last record and return the parsed columns
+    pub fn finish_table(mut self) -> Result<Table> {
+        if self.state == State::Quoted {
+            return Err(self.error(self.record_line, 1, "unterminated quoted field"));
+        }
+        if self.state != State::FieldStart || !self.ends.is_empty() {
+            self.end_record()?;
+        }
+        let columns = self.columns.unwrap_or_default();
+        Table::from_columns(columns.into_iter().map(ColumnBuilder::finish).collect())
+    }
+}
+
+#[wasm_bindgen]
+impl CsvReader {
+    /// `delimiter` is a single ASCII character such as `","`, `";"` or `"\t"`
+    #[wasm_bindgen(constructor)]
+    pub fn new(delimiter: &str, has_header: bool) -> Result<CsvReader, JsError> {
+        let byte = match delimiter.as_bytes() {
+            [b] => *b,
+            _ => {
+                return Err(ComputeError::invalid(format!(
+                    "delimiter must be one ASCII character, got {delimiter:?}"
+                ))
+                .into())
+            }
+        };
+        Ok(CsvReader::with_options(byte, has_header)?)
+    }
+
+    /// Parse the next chunk (a `Uint8Array` of UTF-8 text)
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), JsError> {
+        Ok(self.push_bytes(chunk)?)
+    }
+
+    /// Finish parsing and return the table. The reader is consumed.
+    pub fn finish(self) -> Result<Table, JsError> {
+        Ok(self.finish_table()?)
+    }
+}
+
+/// Parse a complete CSV buffer in one call
+#[wasm_bindgen]
+pub fn parse_csv(bytes: &[u8], delimiter: &str, has_header: bool) -> Result<Table, JsError> {
+    let mut reader = CsvReader::new(delimiter, has_header)?;
+    reader.push(bytes)?;
+    reader.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::{ColumnData, ColumnType, NULL_CODE};
+
+    fn parse_chunked(text: &str, chunk: usize, delimiter: u8, header: bool) -> Result<Table> {
+        let mut reader = CsvReader::with_options(delimiter, header)?;
+        for part in text.as_bytes().chunks(chunk) {
+            reader.push_bytes(part)?;
+        }
+        reader.finish_table()
+    }
+
+    #[test]
+    fn test_quoting_and_types() {
+        let text = "\u{feff}id,name,score,ok\r\n\
+                    1,\"Smith, J\",2.5,true\r\n\
+                    2,\"say \"\"hi\"\"\nagain\",,FALSE\r\n\
+                    \n\
+                    3,Smith,1e3,\r\n";
+        // Every chunk size must give the same table
+        let table = parse_chunked(text, text.len(), b',', true).unwrap();
+        for chunk in 1..8 {
+            assert_eq!(parse_chunked(text, chunk, b',', true).unwrap(), table);
+        }
+
+        assert_eq!(table.row_count(), 3);
+        let names: Vec<&str> = table.columns().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["id", "name", "score", "ok"]);
+        let types: Vec<ColumnType> = table.columns().iter().map(|c| c.column_type()).collect();
+        assert_eq!(
+            types,
+            [
+                ColumnType::Integer,
+                ColumnType::Text,
+                ColumnType::Float,
+                ColumnType::Boolean
+            ]
+        );
+        assert_eq!(
+            table.columns()[0].data(),
+            &ColumnData::Integer(vec![1, 2, 3])
+        );
+        assert_eq!(
+            table.columns()[1].text(1).as_deref(),
+            Some("say \"hi\"\nagain")
+        );
+        let score = table.columns()[2].to_f64().unwrap();
+        assert_eq!(score[0], 2.5);
+        assert!(score[1].is_nan());
+        assert_eq!(score[2], 1000.0);
+        assert_eq!(table.columns()[3].null_count(), 1);
+    }
+
+    #[test]
+    fn test_delimiter_without_header() {
+        let table = parse_chunked("a\t1\nb\tx\na\t", 3, b'\t', false).unwrap();
+        assert_eq!(table.columns()[0].name(), "column_1");
+        assert_eq!(
+            table.columns()[0].data(),
+            &ColumnData::Text {
+                codes: vec![0, 1, 0],
+                dictionary: vec!["a".into(), "b".into()]
+            }
+        );
+        // 1 then "x" widens to text; the trailing empty field is null
+        assert_eq!(
+            table.columns()[1].data(),
+            &ColumnData::Text {
+                codes: vec![0, 1, NULL_CODE],
+                dictionary: vec!["1".into(), "x".into()]
+            }
+        );
+        assert_eq!(parse_chunked("", 1, b',', true).unwrap(), Table::default());
+
+        // Widening to text must not rewrite numbers parsed before or after it
+        let table = parse_chunked("zip\n02134\n1.50\nSW1A\n007\n", 2, b',', true).unwrap();
+        let zip = &table.columns()[0];
+        let texts: Vec<String> = (0..4).filter_map(|i| zip.text(i)).collect();
+        assert_eq!(texts, ["02134", "1.50", "SW1A", "007"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse_chunked("a,b\n1,2\n3\n", 4, b',', true).unwrap_err();
+        assert!(matches!(err, ComputeError::Parse { line: 3, .. }), "{err}");
+
+        let err = parse_chunked("a\n\"x\"y\n", 2, b',', true).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ComputeError::Parse {
+                    line: 2,
+                    column: 4,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        let err = parse_chunked("a\n\"open\n\n", 5, b',', true).unwrap_err();
+        assert!(matches!(err, ComputeError::Parse { line: 2, .. }), "{err}");
+
+        assert!(CsvReader::with_options(b'"', true).is_err());
+    }
+}
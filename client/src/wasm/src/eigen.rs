@@ -0,0 +1,446 @@
+//! Symmetric eigen-decomposition, thin SVD and PCA.
+//!
+//! Both decompositions use Jacobi rotations: cyclic two-sided Jacobi for the
+//! symmetric eigenproblem and one-sided (Hestenes) Jacobi for the SVD. They
+//! are slower than QR-based methods on large inputs but simple, accurate for
+//! small singular values, and fine for the few hundred columns PCA sees here.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, checked_area, ComputeError, Result};
+use crate::linalg::require_symmetric;
+use crate::matrix::Matrix;
+
+/// Upper bound on Jacobi sweeps; convergence is quadratic, so only
+/// non-finite input is expected to hit it
+const MAX_SWEEPS: usize = 64;
+
+fn no_convergence(what: &'static str) -> ComputeError {
+    ComputeError::NoConvergence {
+        what,
+        iterations: MAX_SWEEPS,
+    }
+}
+
+/// Eigenvalues (descending) and matching unit eigenvectors (as columns)
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct SymmetricEigen {
+    values: Vec<f64>,
+    vectors: Matrix,
+}
+
+/// Reorder the columns of `m` (and `values`) by decreasing value
+fn sort_descending(values: Vec<f64>, m: &Matrix) -> Result<(Vec<f64>, Matrix)> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&i, &j| values[j].total_cmp(&values[i]));
+    let mut sorted = Matrix::zeros(m.rows(), order.len())?;
+    let cols = order.len();
+    for r in 0..m.rows() {
+        for (c, &src) in order.iter().enumerate() {
+            sorted.data_mut()[r * cols + c] = m.at(r, src);
+        }
+    }
+    Ok((order.iter().map(|&i| values[i]).collect(), sorted))
+}
+
+impl SymmetricEigen {
+    /// Cyclic Jacobi: rotate away each off-diagonal entry until they vanish
+    pub fn compute(a: &Matrix) -> Result<SymmetricEigen> {
+        let n = require_symmetric(a, "symmetric eigen-decomposition")?;
+        let mut a = a.clone();
+        let mut v = Matrix::identity(n)?;
+        let ad = a.data_mut();
+        let vd = v.data_mut();
+        let norm: f64 = ad.iter().map(|x| x * x).sum();
+
+        for sweep in 0..=MAX_SWEEPS {
+            let off: f64 = (0..n)
+                .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+                .map(|(p, q)| ad[p * n + q] * ad[p * n + q])
+                .sum();
+            if off <= f64::EPSILON * f64::EPSILON * norm {
+                break;
+            }
+            if sweep == MAX_SWEEPS {
+                return Err(no_convergence("symmetric eigen-decomposition"));
+            }
+            for p in 0..n {
+                for q in p + 1..n {
+                    let apq = ad[p * n + q];
+                    if apq == 0.0 {
+                        continue;
+                    }
+                    let theta = (ad[q * n + q] - ad[p * n + p]) / (2.0 * apq);
+                    let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    for k in 0..n {
+                        let (akp, akq) = (ad[k * n + p], ad[k * n + q]);
+                        ad[k * n + p] = c * akp - s * akq;
+                        ad[k * n + q] = s * akp + c * akq;
+                    }
+                    for k in 0..n {
+                        let (apk, aqk) = (ad[p * n + k], ad[q * n + k]);
+                        ad[p * n + k] = c * apk - s * aqk;
+                        ad[q * n + k] = s * apk + c * aqk;
+                    }
+                    for k in 0..n {
+                        let (vkp, vkq) = (vd[k * n + p], vd[k * n + q]);
+                        vd[k * n + p] = c * vkp - s * vkq;
+                        vd[k * n + q] = s * vkp + c * vkq;
+                    }
+                }
+            }
+        }
+
+        let values = (0..n).map(|i| ad[i * n + i]).collect();
+        let (values, vectors) = sort_descending(values, &v)?;
+        Ok(SymmetricEigen { values, vectors })
+    }
+
+    pub fn eigenvalues(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn eigenvectors(&self) -> &Matrix {
+        &self.vectors
+    }
+}
+
+#[wasm_bindgen]
+impl SymmetricEigen {
+    /// Eigenvalues in descending order
+    #[wasm_bindgen(getter)]
+    pub fn values(&self) -> Vec<f64> {
+        self.values.clone()
+    }
+
+    /// `n×n` matrix whose column `i` is the eigenvector for `values[i]`
+    #[wasm_bindgen(getter)]
+    pub fn vectors(&self) -> Matrix {
+        self.vectors.clone()
+    }
+}
+
+/// Thin SVD `A = U diag(S) Vᵀ` with `r = min(m, n)` singular values.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Svd {
+    u: Matrix,
+    s: Vec<f64>,
+    v: Matrix,
+}
+
+impl Svd {
+    pub fn compute(a: &Matrix) -> Result<Svd> {
+        if a.rows() < a.cols() {
+            // Aᵀ = V S Uᵀ
+            let t = Svd::compute(&a.transposed()?)?;
+            return Ok(Svd {
+                u: t.v,
+                s: t.s,
+                v: t.u,
+            });
+        }
+
+        // One-sided Jacobi: orthogonalize the columns of U = A·V
+        let (m, n) = (a.rows(), a.cols());
+        let mut u = a.clone();
+        let mut v = Matrix::identity(n)?;
+        let ud = u.data_mut();
+        let vd = v.data_mut();
+
+        let mut converged = false;
+        for _ in 0..MAX_SWEEPS {
+            let mut rotated = false;
+            for p in 0..n {
+                for q in p + 1..n {
+                    let (mut alpha, mut beta, mut gamma) = (0.0, 0.0, 0.0);
+                    for i in 0..m {
+                        let (up, uq) = (ud[i * n + p], ud[i * n + q]);
+                        alpha += up * up;
+                        beta += uq * uq;
+                        gamma += up * uq;
+                    }
+                    if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+                    let zeta = (beta - alpha) / (2.0 * gamma);
+                    let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                    let c = 1.0 / (1.0 + t * t).sqrt();
+                    let s = c * t;
+                    for i in 0..m {
+                        let (up, uq) = (ud[i * n + p], ud[i * n + q]);
+                        ud[i * n + p] = c * up - s * uq;
+                        ud[i * n + q] = s * up + c * uq;
+                    }
+                    for i in 0..n {
+                        let (vp, vq) = (vd[i * n + p], vd[i * n + q]);
+                        vd[i * n + p] = c * vp - s * vq;
+                        vd[i * n + q] = s * vp + c * vq;
+                    }
+                }
+            }
+            if !rotated {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(no_convergence("SVD"));
+        }
+
+        // Column norms are the singular values; normalize U's columns
+        let s: Vec<f64> = (0..n)
+            .map(|j| {
+                (0..m)
+                    .map(|i| ud[i * n + j] * ud[i * n + j])
+                    .sum::<f64>()
+                    .sqrt()
+            })
+            .collect();
+        for (j, &sigma) in s.iter().enumerate() {
+            if sigma > 0.0 {
+                (0..m).for_each(|i| ud[i * n + j] /= sigma);
+            }
+        }
+
+        let (s_sorted, u) = sort_descending(s.clone(), &u)?;
+        let (_, v) = sort_descending(s, &v)?;
+        Ok(Svd { u, s: s_sorted, v })
+    }
+
+    pub fn singular_values(&self) -> &[f64] {
+        &self.s
+    }
+
+    pub fn left(&self) -> &Matrix {
+        &self.u
+    }
+
+    pub fn right(&self) -> &Matrix {
+        &self.v
+    }
+}
+
+#[wasm_bindgen]
+impl Svd {
+    /// `m×r` left singular vectors (columns)
+    #[wasm_bindgen(getter)]
+    pub fn u(&self) -> Matrix {
+        self.u.clone()
+    }
+
+    /// Singular values in descending order
+    #[wasm_bindgen(getter)]
+    pub fn s(&self) -> Vec<f64> {
+        self.s.clone()
+    }
+
+    /// `n×r` right singular vectors (columns), i.e. `V` rather than `Vᵀ`
+    #[wasm_bindgen(getter)]
+    pub fn v(&self) -> Matrix {
+        self.v.clone()
+    }
+}
+
+/// Principal components of a `rows×cols` data set (one sample per row).
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Pca {
+    mean: Vec<f64>,
+    components: Matrix,
+    explained_variance: Vec<f64>,
+    explained_variance_ratio: Vec<f64>,
+}
+
+impl Pca {
+    /// Keep the top `k` components of the mean-centered data
+    pub fn fit(data: &[f64], rows: usize, cols: usize, k: usize) -> Result<Pca> {
+        check_len("data", data.len(), checked_area(rows, cols)?)?;
+        if rows < 2 {
+            return Err(ComputeError::invalid("PCA needs at least two rows"));
+        }
+        if k == 0 || k > rows.min(cols) {
+            return Err(ComputeError::invalid(format!(
+                "k must be between 1 and {}, got {k}",
+                rows.min(cols)
+            )));
+        }
+
+        let mut mean = vec![0.0; cols];
+        for row in data.chunks_exact(cols) {
+            mean.iter_mut().zip(row).for_each(|(m, x)| *m += x);
+        }
+        mean.iter_mut().for_each(|m| *m /= rows as f64);
+
+        let mut centered = Matrix::from_vec(data.to_vec(), rows, cols)?;
+        for row in centered.data_mut().chunks_exact_mut(cols) {
+            row.iter_mut().zip(&mean).for_each(|(x, m)| *x -= m);
+        }
+
+        let svd = Svd::compute(&centered)?;
+        let variance: Vec<f64> = svd.s.iter().map(|s| s * s / (rows - 1) as f64).collect();
+        let total: f64 = variance.iter().sum();
+
+        let mut components = Matrix::zeros(k, cols)?;
+        for c in 0..k {
+            for j in 0..cols {
+                components.data_mut()[c * cols + j] = svd.v.at(j, c);
+            }
+        }
+        let explained_variance = variance[..k].to_vec();
+        let explained_variance_ratio = explained_variance
+            .iter()
+            .map(|v| if total > 0.0 { v / total } else { 0.0 })
+            .collect();
+
+        Ok(Pca {
+            mean,
+            components,
+            explained_variance,
+            explained_variance_ratio,
+        })
+    }
+
+    /// Project `rows` samples onto the components, giving a `rows×k` matrix
+    pub fn project(&self, data: &[f64], rows: usize) -> Result<Matrix> {
+        let cols = self.mean.len();
+        let mut centered = Matrix::from_vec(data.to_vec(), rows, cols)?;
+        for row in centered.data_mut().chunks_exact_mut(cols) {
+            row.iter_mut().zip(&self.mean).for_each(|(x, m)| *x -= m);
+        }
+        centered.matmul(&self.components.transposed()?)
+    }
+}
+
+#[wasm_bindgen]
+impl Pca {
+    /// `k×cols` matrix, one principal axis per row
+    #[wasm_bindgen(getter)]
+    pub fn components(&self) -> Matrix {
+        self.components.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = explainedVariance)]
+    pub fn explained_variance(&self) -> Vec<f64> {
+        self.explained_variance.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = explainedVarianceRatio)]
+    pub fn explained_variance_ratio(&self) -> Vec<f64> {
+        self.explained_variance_ratio.clone()
+    }
+
+    /// Column means subtracted before projecting
+    #[wasm_bindgen(getter)]
+    pub fn mean(&self) -> Vec<f64> {
+        self.mean.clone()
+    }
+
+    /// Project new samples (row-major, same column count) onto the components
+    pub fn transform(&self, data: &[f64], rows: usize) -> Result<Matrix, JsError> {
+        Ok(self.project(data, rows)?)
+    }
+}
+
+/// Eigen-decomposition of a symmetric matrix
+#[wasm_bindgen(js_name = symmetricEigen)]
+pub fn js_symmetric_eigen(a: &Matrix) -> Result<SymmetricEigen, JsError> {
+    Ok(SymmetricEigen::compute(a)?)
+}
+
+/// Thin singular value decomposition
+#[wasm_bindgen(js_name = svd)]
+pub fn js_svd(a: &Matrix) -> Result<Svd, JsError> {
+    Ok(Svd::compute(a)?)
+}
+
+/// Principal component analysis of row-major `data` (`rows` samples × `cols` features)
+#[wasm_bindgen]
+pub fn pca(data: &[f64], rows: usize, cols: usize, k: usize) -> Result<Pca, JsError> {
+    Ok(Pca::fit(data, rows, cols, k)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mat(data: &[f64], rows: usize, cols: usize) -> Matrix {
+        Matrix::from_vec(data.to_vec(), rows, cols).unwrap()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-9, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let a = mat(&[2.0, 1.0, 0.0, 1.0, 2.0, 1.0, 0.0, 1.0, 2.0], 3, 3);
+        let eig = SymmetricEigen::compute(&a).unwrap();
+        let r2 = 2f64.sqrt();
+        assert_close(eig.eigenvalues(), &[2.0 + r2, 2.0, 2.0 - r2]);
+
+        // A V = V diag(λ)
+        let av = a.matmul(eig.eigenvectors()).unwrap();
+        for (i, &lambda) in eig.eigenvalues().iter().enumerate() {
+            for r in 0..3 {
+                assert!((av.at(r, i) - lambda * eig.eigenvectors().at(r, i)).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_convergence() {
+        let a = mat(&[f64::NAN, 1.0, 1.0, 2.0], 2, 2);
+        assert!(matches!(
+            SymmetricEigen::compute(&a),
+            Err(ComputeError::NoConvergence { .. })
+        ));
+        assert!(matches!(
+            Svd::compute(&a),
+            Err(ComputeError::NoConvergence { .. })
+        ));
+    }
+
+    #[test]
+    fn test_svd_reconstructs() {
+        for a in [
+            mat(&[3.0, 2.0, 2.0, 2.0, 3.0, -2.0], 2, 3),
+            mat(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0], 4, 2),
+        ] {
+            let svd = Svd::compute(&a).unwrap();
+            let r = svd.singular_values().len();
+            assert_eq!(r, 2);
+            let mut us = svd.left().clone();
+            for row in us.data_mut().chunks_exact_mut(r) {
+                row.iter_mut()
+                    .zip(svd.singular_values())
+                    .for_each(|(x, s)| *x *= s);
+            }
+            let rebuilt = us.matmul(&svd.right().transposed().unwrap()).unwrap();
+            assert_close(rebuilt.data(), a.data());
+        }
+        let svd = Svd::compute(&mat(&[3.0, 2.0, 2.0, 2.0, 3.0, -2.0], 2, 3)).unwrap();
+        assert_close(svd.singular_values(), &[5.0, 3.0]);
+    }
+
+    #[test]
+    fn test_pca() {
+        // Points on the line y = 2x: one component explains everything
+        let data = [0.0, 0.0, 1.0, 2.0, 2.0, 4.0, 3.0, 6.0];
+        let pca = Pca::fit(&data, 4, 2, 1).unwrap();
+        let axis = pca.components.row(0);
+        let norm = 5f64.sqrt();
+        assert!((axis[0].abs() - 1.0 / norm).abs() < 1e-12);
+        assert!((axis[1].abs() - 2.0 / norm).abs() < 1e-12);
+        assert_close(&pca.explained_variance_ratio, &[1.0]);
+        assert_eq!(pca.project(&data, 4).unwrap().cols(), 1);
+        assert!(Pca::fit(&data, 4, 2, 3).is_err());
+    }
+}
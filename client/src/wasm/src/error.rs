@@ -0,0 +1,106 @@
+use std::fmt;
+
+/// Errors reported by the compute kernels.
+///
+/// Exports convert these into a `JsError`, so the worker receives a regular
+/// `Error` with a readable message instead of a trapped WASM instance.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComputeError {
+    /// An input buffer does not have the length implied by its dimensions
+    DimensionMismatch {
+        what: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// An argument is outside the domain accepted by the function
+    InvalidArgument(String),
+    /// An integer or floating point result does not fit in its output type
+    Overflow(&'static str),
+    /// A buffer of the requested size could not be allocated
+    AllocationFailure { bytes: usize },
+    /// A matrix that must be inverted is singular to working precision
+    SingularMatrix,
+    /// Cholesky factorization hit a non-positive pivot
+    NotPositiveDefinite,
+    /// An iterative method did not converge within its iteration limit
+    NoConvergence {
+        what: &'static str,
+        iterations: usize,
+    },
+    /// Malformed text input; `line` and `column` are 1-based, the column in bytes
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+pub type Result<T, E = ComputeError> = std::result::Result<T, E>;
+
+impl ComputeError {
+    pub(crate) fn invalid(msg: impl Into<String>) -> Self {
+        ComputeError::InvalidArgument(msg.into())
+    }
+}
+
+impl fmt::Display for ComputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputeError::DimensionMismatch {
+                what,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "dimension mismatch: {what} has length {actual}, expected {expected}"
+            ),
+            ComputeError::InvalidArgument(msg) => write!(f, "invalid argument: {msg}"),
+            ComputeError::Overflow(what) => write!(f, "overflow: {what}"),
+            ComputeError::AllocationFailure { bytes } => {
+                write!(f, "allocation failure: could not allocate {bytes} bytes")
+            }
+            ComputeError::SingularMatrix => write!(f, "matrix is singular to working precision"),
+            ComputeError::NotPositiveDefinite => write!(f, "matrix is not positive definite"),
+            ComputeError::NoConvergence { what, iterations } => {
+                write!(f, "{what} did not converge in {iterations} iterations")
+            }
+            ComputeError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "parse error at line {line}, column {column}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ComputeError {}
+
+/// Check that a buffer has exactly `expected` elements.
+pub(crate) fn check_len(what: &'static str, actual: usize, expected: usize) -> Result<()> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(ComputeError::DimensionMismatch {
+            what,
+            expected,
+            actual,
+        })
+    }
+}
+
+/// `rows * cols` with overflow reported as an error.
+pub(crate) fn checked_area(rows: usize, cols: usize) -> Result<usize> {
+    rows.checked_mul(cols)
+        .ok_or(ComputeError::Overflow("matrix dimensions"))
+}
+
+/// Allocate a buffer filled with `value`, reporting failure instead of aborting.
+pub(crate) fn try_filled<T: Clone>(len: usize, value: T) -> Result<Vec<T>> {
+    let mut buf = Vec::new();
+    buf.try_reserve_exact(len)
+        .map_err(|_| ComputeError::AllocationFailure {
+            bytes: len.saturating_mul(std::mem::size_of::<T>()),
+        })?;
+    buf.resize(len, value);
+    Ok(buf)
+}
@@ -0,0 +1,98 @@
+//! Fibonacci numbers via fast doubling.
+//!
+//! F(2k)   = F(k) * (2*F(k+1) - F(k))
+//! F(2k+1) = F(k)^2 + F(k+1)^2
+//!
+//! Walking the bits of `n` from the top gives O(log n) steps, which matters
+//! once the values are big integers and each step is a multiplication.
+
+use num_bigint::BigUint;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ComputeError, Result};
+
+/// Largest `n` whose Fibonacci number fits in a `u64`
+pub const MAX_U64_N: u32 = 93;
+
+/// Largest `n` accepted by the arbitrary-precision variants (~209k digits)
+pub const MAX_BIG_N: u32 = 1_000_000;
+
+/// F(n) as a `u64`, or `Overflow` for n > 93
+pub fn checked(n: u32) -> Result<u64> {
+    if n > MAX_U64_N {
+        return Err(ComputeError::Overflow("fibonacci exceeds u64"));
+    }
+    // F(94) is the largest intermediate and fits easily in u128
+    let (mut a, mut b) = (0u128, 1u128);
+    for bit in (0..u32::BITS - n.leading_zeros()).rev() {
+        let c = a * (2 * b - a);
+        let d = a * a + b * b;
+        (a, b) = if (n >> bit) & 1 == 0 {
+            (c, d)
+        } else {
+            (d, c + d)
+        };
+    }
+    Ok(a as u64)
+}
+
+/// F(n) as an exact big integer
+pub fn big(n: u32) -> Result<BigUint> {
+    if n > MAX_BIG_N {
+        return Err(ComputeError::invalid(format!(
+            "fibonacci index {n} exceeds the limit of {MAX_BIG_N}"
+        )));
+    }
+    let (mut a, mut b) = (BigUint::ZERO, BigUint::from(1u32));
+    for bit in (0..u32::BITS - n.leading_zeros()).rev() {
+        let c = &a * ((&b << 1u32) - &a);
+        let d = &a * &a + &b * &b;
+        (a, b) = if (n >> bit) & 1 == 0 {
+            (c, d)
+        } else {
+            let e = &c + &d;
+            (d, e)
+        };
+    }
+    Ok(a)
+}
+
+/// Exact Fibonacci number as a decimal string
+#[wasm_bindgen]
+pub fn fibonacci_decimal(n: u32) -> Result<String, JsError> {
+    Ok(big(n)?.to_string())
+}
+
+/// Exact Fibonacci number as a JS `BigInt`
+#[wasm_bindgen]
+pub fn fibonacci_bigint(n: u32) -> Result<js_sys::BigInt, JsError> {
+    let digits = big(n)?.to_string();
+    js_sys::BigInt::new(&JsValue::from_str(&digits))
+        .map_err(|_| JsError::new("failed to convert fibonacci result to BigInt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_matches_iteration() {
+        let (mut a, mut b) = (0u64, 1u64);
+        for n in 0..=MAX_U64_N {
+            assert_eq!(checked(n).unwrap(), a);
+            (a, b) = (b, a.wrapping_add(b));
+        }
+        assert_eq!(
+            checked(94),
+            Err(ComputeError::Overflow("fibonacci exceeds u64"))
+        );
+    }
+
+    #[test]
+    fn test_big() {
+        assert_eq!(big(0).unwrap().to_string(), "0");
+        assert_eq!(big(93).unwrap(), BigUint::from(checked(93).unwrap()));
+        assert_eq!(big(100).unwrap().to_string(), "354224848179261915075");
+        assert!(big(MAX_BIG_N + 1).is_err());
+    }
+}
@@ -0,0 +1,403 @@
+//! Convolution filters for RGBA buffers, applied in place.
+//!
+//! Kernels are applied as written (correlation, not flipped) and centred on
+//! the middle tap, so they must have odd width and height. The generic
+//! `convolve`, `sharpen`, `emboss` and `sobel` filter colour only and keep
+//! alpha; the blurs also blur alpha, in premultiplied space.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, checked_area, try_filled, ComputeError, Result};
+use crate::image::{check_image, premultiplied, store_premultiplied, to_u8, EdgeMode};
+
+/// Correlate the first `channels` of each `stride`-sized pixel with `kernel`;
+/// other channels are copied from `src`
+#[allow(clippy::too_many_arguments)]
+fn correlate(
+    src: &[f32],
+    stride: usize,
+    channels: usize,
+    width: usize,
+    height: usize,
+    kernel: &[f32],
+    kernel_width: usize,
+    kernel_height: usize,
+    edge: EdgeMode,
+) -> Result<Vec<f32>> {
+    let (rx, ry) = (kernel_width / 2, kernel_height / 2);
+    let xs = edge.table(width, rx, rx)?;
+    let ys = edge.table(height, ry, ry)?;
+    let mut out = src.to_vec();
+    let mut acc = [0.0f32; 4];
+    for y in 0..height {
+        for x in 0..width {
+            acc[..channels].fill(0.0);
+            for (ky, sy) in ys[y..y + kernel_height].iter().enumerate() {
+                let Some(sy) = sy else { continue };
+                let row = &kernel[ky * kernel_width..(ky + 1) * kernel_width];
+                for (k, sx) in row.iter().zip(&xs[x..x + kernel_width]) {
+                    let Some(sx) = sx else { continue };
+                    let p = (sy * width + sx) * stride;
+                    for (a, v) in acc[..channels].iter_mut().zip(&src[p..p + channels]) {
+                        *a += k * v;
+                    }
+                }
+            }
+            let p = (y * width + x) * stride;
+            out[p..p + channels].copy_from_slice(&acc[..channels]);
+        }
+    }
+    Ok(out)
+}
+
+/// Separable pass over all four channels: `kx` along rows, then `ky` along columns
+fn separable(
+    src: &[f32],
+    width: usize,
+    height: usize,
+    kx: &[f32],
+    ky: &[f32],
+    edge: EdgeMode,
+) -> Result<Vec<f32>> {
+    let horizontal = correlate(src, 4, 4, width, height, kx, kx.len(), 1, edge)?;
+    correlate(&horizontal, 4, 4, width, height, ky, 1, ky.len(), edge)
+}
+
+fn check_kernel(kernel: &[f32], kernel_width: u32, kernel_height: u32) -> Result<()> {
+    if kernel_width.is_multiple_of(2) || kernel_height.is_multiple_of(2) {
+        return Err(ComputeError::invalid(format!(
+            "kernel dimensions must be odd, got {kernel_width}×{kernel_height}"
+        )));
+    }
+    let taps = checked_area(kernel_width as usize, kernel_height as usize)?;
+    check_len("kernel", kernel.len(), taps)?;
+    if kernel.iter().any(|k| !k.is_finite()) {
+        return Err(ComputeError::invalid("kernel weights must be finite"));
+    }
+    Ok(())
+}
+
+/// Filter the RGB channels of `data` with a `kernel_width × kernel_height` kernel
+pub fn convolve_rgb(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    kernel: &[f32],
+    kernel_width: u32,
+    kernel_height: u32,
+    edge: EdgeMode,
+) -> Result<()> {
+    check_image(data, width, height)?;
+    check_kernel(kernel, kernel_width, kernel_height)?;
+    if data.is_empty() {
+        return Ok(());
+    }
+    let src: Vec<f32> = data.iter().map(|&b| b as f32).collect();
+    let out = correlate(
+        &src,
+        4,
+        3,
+        width as usize,
+        height as usize,
+        kernel,
+        kernel_width as usize,
+        kernel_height as usize,
+        edge,
+    )?;
+    for (b, v) in data.iter_mut().zip(out) {
+        *b = to_u8(v);
+    }
+    Ok(())
+}
+
+/// Blur all four channels with the separable kernel `k` (both axes)
+fn blur(data: &mut [u8], width: u32, height: u32, k: &[f32], edge: EdgeMode) -> Result<()> {
+    check_image(data, width, height)?;
+    if data.is_empty() {
+        return Ok(());
+    }
+    let out = separable(
+        &premultiplied(data),
+        width as usize,
+        height as usize,
+        k,
+        k,
+        edge,
+    )?;
+    store_premultiplied(&out, data);
+    Ok(())
+}
+
+/// Longest blur kernel accepted (64 MiB of weights); anything longer is a
+/// mistake, and would take `width × height × taps` work per pass
+const MAX_TAPS: f64 = (1 << 24) as f64;
+
+/// Kernel length `2 radius + 1`
+fn taps(radius: f64) -> Result<usize> {
+    let taps = 2.0 * radius + 1.0;
+    if taps > MAX_TAPS {
+        return Err(ComputeError::invalid(format!(
+            "blur radius {radius} exceeds the {MAX_TAPS} tap limit"
+        )));
+    }
+    Ok(taps as usize)
+}
+
+/// Normalized Gaussian taps out to 3σ
+pub fn gaussian_kernel(sigma: f32) -> Result<Vec<f32>> {
+    if !(sigma.is_finite() && sigma > 0.0) {
+        return Err(ComputeError::invalid(format!(
+            "blur sigma must be positive, got {sigma}"
+        )));
+    }
+    let radius = (3.0 * f64::from(sigma)).ceil();
+    let mut k = try_filled(taps(radius)?, 0.0f32)?;
+    for (i, w) in k.iter_mut().enumerate() {
+        let x = i as f32 - radius as f32;
+        *w = (-(x * x) / (2.0 * sigma * sigma)).exp();
+    }
+    let total: f32 = k.iter().sum();
+    k.iter_mut().for_each(|w| *w /= total);
+    Ok(k)
+}
+
+pub fn gaussian_blur_rgba(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    sigma: f32,
+    edge: EdgeMode,
+) -> Result<()> {
+    blur(data, width, height, &gaussian_kernel(sigma)?, edge)
+}
+
+pub fn box_blur_rgba(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    radius: u32,
+    edge: EdgeMode,
+) -> Result<()> {
+    let taps = taps(f64::from(radius))?;
+    blur(
+        data,
+        width,
+        height,
+        &try_filled(taps, 1.0 / taps as f32)?,
+        edge,
+    )
+}
+
+/// 3×3 Laplacian sharpen; `amount` 0 leaves the image unchanged
+pub fn sharpen_kernel(amount: f32) -> Result<[f32; 9]> {
+    if !(amount.is_finite() && amount >= 0.0) {
+        return Err(ComputeError::invalid(format!(
+            "sharpen amount must be finite and non-negative, got {amount}"
+        )));
+    }
+    let a = -amount;
+    Ok([0.0, a, 0.0, a, 1.0 + 4.0 * amount, a, 0.0, a, 0.0])
+}
+
+pub const EMBOSS: [f32; 9] = [-2.0, -1.0, 0.0, -1.0, 1.0, 1.0, 0.0, 1.0, 2.0];
+
+/// Replace RGB with the Sobel gradient magnitude of the luminance
+pub fn sobel_rgba(data: &mut [u8], width: u32, height: u32, edge: EdgeMode) -> Result<()> {
+    check_image(data, width, height)?;
+    if data.is_empty() {
+        return Ok(());
+    }
+    let (w, h) = (width as usize, height as usize);
+    let luma: Vec<f32> = data
+        .chunks_exact(4)
+        .map(|p| 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32)
+        .collect();
+    const GX: [f32; 9] = [-1.0, 0.0, 1.0, -2.0, 0.0, 2.0, -1.0, 0.0, 1.0];
+    const GY: [f32; 9] = [-1.0, -2.0, -1.0, 0.0, 0.0, 0.0, 1.0, 2.0, 1.0];
+    let gx = correlate(&luma, 1, 1, w, h, &GX, 3, 3, edge)?;
+    let gy = correlate(&luma, 1, 1, w, h, &GY, 3, 3, edge)?;
+    for ((px, x), y) in data.chunks_exact_mut(4).zip(gx).zip(gy) {
+        let m = to_u8(x.hypot(y));
+        px[..3].fill(m);
+    }
+    Ok(())
+}
+
+/// Convolve the RGB channels of `ImageData` with a row-major kernel (odd width/height)
+#[wasm_bindgen]
+pub fn convolve(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    kernel: &[f32],
+    kernel_width: u32,
+    kernel_height: u32,
+    edge: EdgeMode,
+) -> Result<(), JsError> {
+    Ok(convolve_rgb(
+        data,
+        width,
+        height,
+        kernel,
+        kernel_width,
+        kernel_height,
+        edge,
+    )?)
+}
+
+/// Gaussian blur (two separable passes) with standard deviation `sigma` pixels
+#[wasm_bindgen]
+pub fn gaussian_blur(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    sigma: f32,
+    edge: EdgeMode,
+) -> Result<(), JsError> {
+    Ok(gaussian_blur_rgba(data, width, height, sigma, edge)?)
+}
+
+/// Mean over a `(2 * radius + 1)²` square
+#[wasm_bindgen]
+pub fn box_blur(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    radius: u32,
+    edge: EdgeMode,
+) -> Result<(), JsError> {
+    Ok(box_blur_rgba(data, width, height, radius, edge)?)
+}
+
+/// Sharpen by `amount` (1 is a typical strength)
+#[wasm_bindgen]
+pub fn sharpen(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    amount: f32,
+    edge: EdgeMode,
+) -> Result<(), JsError> {
+    Ok(convolve_rgb(
+        data,
+        width,
+        height,
+        &sharpen_kernel(amount)?,
+        3,
+        3,
+        edge,
+    )?)
+}
+
+/// Sobel edge detection: grey gradient magnitude, alpha kept
+#[wasm_bindgen]
+pub fn sobel(data: &mut [u8], width: u32, height: u32, edge: EdgeMode) -> Result<(), JsError> {
+    Ok(sobel_rgba(data, width, height, edge)?)
+}
+
+#[wasm_bindgen]
+pub fn emboss(data: &mut [u8], width: u32, height: u32, edge: EdgeMode) -> Result<(), JsError> {
+    Ok(convolve_rgb(data, width, height, &EMBOSS, 3, 3, edge)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(w: usize, h: usize, f: impl Fn(usize, usize) -> [u8; 4]) -> Vec<u8> {
+        let f = &f;
+        (0..h)
+            .flat_map(|y| (0..w).flat_map(move |x| f(x, y)))
+            .collect()
+    }
+
+    #[test]
+    fn test_identity_and_uniform() {
+        let img = image(5, 4, |x, y| [(x * 40) as u8, (y * 60) as u8, 7, 200]);
+        let mut out = img.clone();
+        let mut id = [0.0; 9];
+        id[4] = 1.0;
+        convolve_rgb(&mut out, 5, 4, &id, 3, 3, EdgeMode::Zero).unwrap();
+        assert_eq!(out, img);
+
+        let flat = image(6, 3, |_, _| [90, 30, 200, 255]);
+        for edge in [EdgeMode::Clamp, EdgeMode::Wrap, EdgeMode::Mirror] {
+            let mut out = flat.clone();
+            box_blur_rgba(&mut out, 6, 3, 2, edge).unwrap();
+            assert_eq!(out, flat);
+            gaussian_blur_rgba(&mut out, 6, 3, 1.5, edge).unwrap();
+            assert_eq!(out, flat);
+            convolve_rgb(&mut out, 6, 3, &sharpen_kernel(1.0).unwrap(), 3, 3, edge).unwrap();
+            assert_eq!(out, flat);
+        }
+    }
+
+    #[test]
+    fn test_blur_ignores_transparent_colour() {
+        // Opaque red next to transparent green: the blur must not turn red green
+        let mut img = image(4, 1, |x, _| {
+            if x < 2 {
+                [255, 0, 0, 255]
+            } else {
+                [0, 255, 0, 0]
+            }
+        });
+        gaussian_blur_rgba(&mut img, 4, 1, 1.0, EdgeMode::Clamp).unwrap();
+        for px in img.chunks_exact(4) {
+            if px[3] > 0 {
+                assert_eq!(&px[..3], &[255, 0, 0]);
+            }
+        }
+        assert!(img[3] > img[7] && img[7] > img[11] && img[11] > img[15]);
+    }
+
+    #[test]
+    fn test_sobel_step_edge() {
+        let mut img = image(6, 3, |x, _| {
+            if x < 3 {
+                [0, 0, 0, 255]
+            } else {
+                [255, 255, 255, 255]
+            }
+        });
+        sobel_rgba(&mut img, 6, 3, EdgeMode::Clamp).unwrap();
+        let row: Vec<u8> = img[..24].chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(row, [0, 0, 255, 255, 0, 0]);
+        assert!(img.chunks_exact(4).all(|p| p[3] == 255));
+    }
+
+    #[test]
+    fn test_invalid_kernels() {
+        let mut img = vec![0u8; 16];
+        assert!(convolve_rgb(&mut img, 2, 2, &[1.0; 4], 2, 2, EdgeMode::Clamp).is_err());
+        assert!(convolve_rgb(&mut img, 2, 2, &[1.0; 8], 3, 3, EdgeMode::Clamp).is_err());
+        assert!(convolve_rgb(&mut img, 3, 2, &[1.0], 1, 1, EdgeMode::Clamp).is_err());
+        assert!(gaussian_kernel(0.0).is_err());
+        assert!(sharpen_kernel(f32::NAN).is_err());
+        // Oversized kernels are errors, not aborts
+        assert!(gaussian_kernel(1e9).is_err());
+        assert!(gaussian_kernel(f32::MAX).is_err());
+        assert!(box_blur_rgba(&mut img, 2, 2, u32::MAX, EdgeMode::Clamp).is_err());
+        // 65537² wraps to 131073 in 32-bit arithmetic
+        let wrapped = vec![0.0; 131_073];
+        assert!(convolve_rgb(&mut img, 2, 2, &wrapped, 65_537, 65_537, EdgeMode::Clamp).is_err());
+        assert!(check_kernel(&[], u32::MAX, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn test_empty_images() {
+        for edge in [
+            EdgeMode::Clamp,
+            EdgeMode::Wrap,
+            EdgeMode::Mirror,
+            EdgeMode::Zero,
+        ] {
+            for (w, h) in [(0, 5), (5, 0)] {
+                gaussian_blur_rgba(&mut [], w, h, 1.0, edge).unwrap();
+                box_blur_rgba(&mut [], w, h, 2, edge).unwrap();
+                convolve_rgb(&mut [], w, h, &[1.0; 9], 3, 3, edge).unwrap();
+                sobel_rgba(&mut [], w, h, edge).unwrap();
+            }
+        }
+    }
+}
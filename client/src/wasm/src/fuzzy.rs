@@ -0,0 +1,222 @@
+//! Approximate string matching for type-ahead.
+//!
+//! Distances count Unicode scalar values (`char`s), not UTF-16 code units,
+//! so "é" is one edit away from "e" whichever way JS encoded it.
+
+use std::collections::{HashMap, HashSet};
+
+use wasm_bindgen::prelude::*;
+
+use crate::search::SearchHits;
+
+/// Insertions, deletions and substitutions needed to turn `a` into `b`
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, &ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// Levenshtein distance that also counts adjacent transpositions as one edit
+///
+/// This is the unrestricted variant (Lowrance–Wagner), so a substring may be
+/// edited again after a transposition: `damerau_levenshtein("ca", "abc") == 2`.
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (n, m) = (a.len(), b.len());
+    let max = n + m;
+    let width = m + 2;
+    // d[(i + 1) * width + (j + 1)] is the distance between a[..i] and b[..j]
+    let mut d = vec![0usize; (n + 2) * width];
+    d[0] = max;
+    for i in 0..=n {
+        d[(i + 1) * width] = max;
+        d[(i + 1) * width + 1] = i;
+    }
+    for j in 0..=m {
+        d[j + 1] = max;
+        d[width + j + 1] = j;
+    }
+
+    let mut last_row: HashMap<char, usize> = HashMap::new();
+    for i in 1..=n {
+        let mut last_match_col = 0;
+        for j in 1..=m {
+            let i1 = last_row.get(&b[j - 1]).copied().unwrap_or(0);
+            let j1 = last_match_col;
+            let cost = if a[i - 1] == b[j - 1] {
+                last_match_col = j;
+                0
+            } else {
+                1
+            };
+            d[(i + 1) * width + j + 1] = (d[i * width + j] + cost)
+                .min(d[(i + 1) * width + j] + 1)
+                .min(d[i * width + j + 1] + 1)
+                .min(d[i1 * width + j1] + (i - i1 - 1) + 1 + (j - j1 - 1));
+        }
+        last_row.insert(a[i - 1], i);
+    }
+    d[(n + 1) * width + m + 1]
+}
+
+/// Jaro similarity in `[0, 1]`
+fn jaro(a: &[char], b: &[char]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_used = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, &ca) in a.iter().enumerate() {
+        let lo = i.saturating_sub(window);
+        let hi = (i + window + 1).min(b.len());
+        if let Some(j) = (lo..hi).find(|&j| !b_used[j] && b[j] == ca) {
+            b_used[j] = true;
+            a_matches.push(ca);
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+    let b_matches = b.iter().zip(&b_used).filter(|(_, &u)| u).map(|(&c, _)| c);
+    let transpositions = a_matches
+        .iter()
+        .zip(b_matches)
+        .filter(|(x, y)| **x != *y)
+        .count();
+    let m = a_matches.len() as f64;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64 / 2.0) / m) / 3.0
+}
+
+/// Jaro-Winkler similarity in `[0, 1]`, boosting a shared prefix of up to 4 chars
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let sim = jaro(&a, &b);
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    sim + prefix as f64 * 0.1 * (1.0 - sim)
+}
+
+/// Lowercased trigrams of each word, padded like PostgreSQL's `pg_trgm`
+fn trigrams(s: &str) -> HashSet<[char; 3]> {
+    let mut grams = HashSet::new();
+    for word in s
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let padded: Vec<char> = "  "
+            .chars()
+            .chain(word.chars().flat_map(char::to_lowercase))
+            .chain(" ".chars())
+            .collect();
+        grams.extend(padded.windows(3).map(|w| [w[0], w[1], w[2]]));
+    }
+    grams
+}
+
+/// Shared trigrams over all distinct trigrams (Jaccard), in `[0, 1]`
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (ta, tb) = (trigrams(a), trigrams(b));
+    let union = ta.union(&tb).count();
+    if union == 0 {
+        return 0.0;
+    }
+    ta.intersection(&tb).count() as f64 / union as f64
+}
+
+/// Top `limit` candidates by case-insensitive Jaro-Winkler similarity to `query`
+pub fn top_matches(candidates: &[String], query: &str, limit: usize) -> SearchHits {
+    let query = query.to_lowercase();
+    let scores: Vec<f64> = candidates
+        .iter()
+        .map(|c| jaro_winkler(&c.to_lowercase(), &query))
+        .collect();
+    let ids = (0..candidates.len() as u32)
+        .filter(|&i| scores[i as usize] > 0.0)
+        .collect();
+    SearchHits::top(ids, &scores, limit)
+}
+
+/// Levenshtein edit distance
+#[wasm_bindgen]
+pub fn edit_distance(a: &str, b: &str) -> u32 {
+    levenshtein(a, b) as u32
+}
+
+/// Damerau-Levenshtein distance (transpositions count as one edit)
+#[wasm_bindgen(js_name = damerau_levenshtein)]
+pub fn js_damerau_levenshtein(a: &str, b: &str) -> u32 {
+    damerau_levenshtein(a, b) as u32
+}
+
+/// Jaro-Winkler similarity in `[0, 1]`
+#[wasm_bindgen(js_name = jaro_winkler)]
+pub fn js_jaro_winkler(a: &str, b: &str) -> f64 {
+    jaro_winkler(a, b)
+}
+
+/// Trigram (Jaccard) similarity in `[0, 1]`
+#[wasm_bindgen(js_name = trigram_similarity)]
+pub fn js_trigram_similarity(a: &str, b: &str) -> f64 {
+    trigram_similarity(a, b)
+}
+
+/// Best `limit` fuzzy matches of `query` among `candidates`, with scores
+#[wasm_bindgen]
+pub fn fuzzy_search(candidates: Vec<String>, query: &str, limit: usize) -> SearchHits {
+    top_matches(&candidates, query, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distances() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+        assert_eq!(levenshtein("ab", "ba"), 2);
+        assert_eq!(damerau_levenshtein("ab", "ba"), 1);
+        assert_eq!(damerau_levenshtein("ca", "abc"), 2);
+        assert_eq!(damerau_levenshtein("kitten", "sitting"), 3);
+        assert_eq!(damerau_levenshtein("", ""), 0);
+    }
+
+    #[test]
+    fn test_similarities() {
+        assert!((jaro_winkler("MARTHA", "MARHTA") - 0.9611).abs() < 1e-4);
+        assert!((jaro_winkler("DIXON", "DICKSONX") - 0.8133).abs() < 1e-4);
+        assert_eq!(jaro_winkler("same", "same"), 1.0);
+        assert_eq!(jaro_winkler("abc", ""), 0.0);
+
+        assert_eq!(trigram_similarity("word", "WORD"), 1.0);
+        // "cat": {"  c", " ca", "cat", "at "}, "cart" shares 2 of 7 distinct trigrams
+        assert!((trigram_similarity("cat", "cart") - 2.0 / 7.0).abs() < 1e-12);
+        assert_eq!(trigram_similarity("", ""), 0.0);
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let candidates: Vec<String> = ["Apple", "Application", "Banana", "Maple"]
+            .map(String::from)
+            .to_vec();
+        let hits = top_matches(&candidates, "appl", 2);
+        assert_eq!(hits.hit_indices(), &[0, 1]);
+        assert!(hits.hit_scores()[0] > hits.hit_scores()[1]);
+    }
+}
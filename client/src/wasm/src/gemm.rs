@@ -0,0 +1,211 @@
+//! Blocked general matrix multiplication (`C = A · B`) for row-major buffers.
+//!
+//! The loops are tiled so that a panel of `B` stays in cache while rows of
+//! `A` stream past it, and the innermost loop is a contiguous `y += a * x`
+//! over a row of `B`. That inner loop is the only place that differs between
+//! the scalar build and the `simd` feature on `wasm32` with `simd128`.
+//!
+//! Accumulation still happens in increasing `k` order, so results are
+//! bit-identical to the naive triple loop.
+
+use std::fmt::Debug;
+use std::ops::{Add, Mul};
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, checked_area, try_filled, Result};
+
+/// Rows of `A` per tile
+const BLOCK_M: usize = 64;
+/// Shared dimension per tile
+const BLOCK_K: usize = 128;
+/// Columns of `B` per tile
+const BLOCK_N: usize = 256;
+
+/// Floating point types the kernel is implemented for
+pub trait Element:
+    Copy + Default + Debug + PartialEq + Add<Output = Self> + Mul<Output = Self>
+{
+    /// `y[i] += alpha * x[i]` over two slices of equal length
+    fn axpy(alpha: Self, x: &[Self], y: &mut [Self]);
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+macro_rules! scalar_axpy {
+    () => {
+        fn axpy(alpha: Self, x: &[Self], y: &mut [Self]) {
+            for (y, &x) in y.iter_mut().zip(x) {
+                *y += alpha * x;
+            }
+        }
+    };
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+impl Element for f64 {
+    scalar_axpy!();
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+impl Element for f32 {
+    scalar_axpy!();
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+mod simd {
+    use core::arch::wasm32::*;
+
+    use super::Element;
+
+    impl Element for f64 {
+        fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+            let len = x.len().min(y.len());
+            let a = f64x2_splat(alpha);
+            let mut i = 0;
+            while i + 2 <= len {
+                // SAFETY: i + 2 <= len keeps both 16-byte accesses in bounds;
+                // v128_load/v128_store have no alignment requirement.
+                unsafe {
+                    let px = x.as_ptr().add(i) as *const v128;
+                    let py = y.as_mut_ptr().add(i) as *mut v128;
+                    v128_store(py, f64x2_add(v128_load(py), f64x2_mul(a, v128_load(px))));
+                }
+                i += 2;
+            }
+            for j in i..len {
+                y[j] += alpha * x[j];
+            }
+        }
+    }
+
+    impl Element for f32 {
+        fn axpy(alpha: f32, x: &[f32], y: &mut [f32]) {
+            let len = x.len().min(y.len());
+            let a = f32x4_splat(alpha);
+            let mut i = 0;
+            while i + 4 <= len {
+                // SAFETY: as above, with four lanes
+                unsafe {
+                    let px = x.as_ptr().add(i) as *const v128;
+                    let py = y.as_mut_ptr().add(i) as *mut v128;
+                    v128_store(py, f32x4_add(v128_load(py), f32x4_mul(a, v128_load(px))));
+                }
+                i += 4;
+            }
+            for j in i..len {
+                y[j] += alpha * x[j];
+            }
+        }
+    }
+}
+
+/// Multiply an `m×k` matrix by a `k×n` matrix, both row-major.
+pub fn multiply<T: Element>(a: &[T], b: &[T], m: usize, k: usize, n: usize) -> Result<Vec<T>> {
+    check_len("a", a.len(), checked_area(m, k)?)?;
+    check_len("b", b.len(), checked_area(k, n)?)?;
+
+    let mut c = try_filled(checked_area(m, n)?, T::default())?;
+
+    for j0 in (0..n).step_by(BLOCK_N) {
+        let j1 = (j0 + BLOCK_N).min(n);
+        for p0 in (0..k).step_by(BLOCK_K) {
+            let p1 = (p0 + BLOCK_K).min(k);
+            for i0 in (0..m).step_by(BLOCK_M) {
+                let i1 = (i0 + BLOCK_M).min(m);
+                for i in i0..i1 {
+                    let c_row = &mut c[i * n + j0..i * n + j1];
+                    for p in p0..p1 {
+                        T::axpy(a[i * k + p], &b[p * n + j0..p * n + j1], c_row);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(c)
+}
+
+/// Reference i-j-k triple loop, kept for tests and benchmarks.
+pub fn multiply_naive<T: Element>(
+    a: &[T],
+    b: &[T],
+    m: usize,
+    k: usize,
+    n: usize,
+) -> Result<Vec<T>> {
+    check_len("a", a.len(), checked_area(m, k)?)?;
+    check_len("b", b.len(), checked_area(k, n)?)?;
+
+    let mut c = try_filled(checked_area(m, n)?, T::default())?;
+    for i in 0..m {
+        for j in 0..n {
+            let mut sum = T::default();
+            for p in 0..k {
+                sum = sum + a[i * k + p] * b[p * n + j];
+            }
+            c[i * n + j] = sum;
+        }
+    }
+    Ok(c)
+}
+
+/// General matrix multiplication: `m×k` times `k×n` (f64, row-major)
+#[wasm_bindgen]
+pub fn gemm_f64(a: &[f64], b: &[f64], m: usize, k: usize, n: usize) -> Result<Vec<f64>, JsError> {
+    Ok(multiply(a, b, m, k, n)?)
+}
+
+/// General matrix multiplication: `m×k` times `k×n` (f32, row-major)
+#[wasm_bindgen]
+pub fn gemm_f32(a: &[f32], b: &[f32], m: usize, k: usize, n: usize) -> Result<Vec<f32>, JsError> {
+    Ok(multiply(a, b, m, k, n)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ComputeError;
+
+    fn sequence(len: usize) -> Vec<f64> {
+        (0..len).map(|i| ((i * 7 % 13) as f64) - 6.5).collect()
+    }
+
+    #[test]
+    fn test_matches_naive_across_block_edges() {
+        for &(m, k, n) in &[(1, 1, 1), (3, 5, 2), (65, 130, 257), (7, 300, 1)] {
+            let a = sequence(m * k);
+            let b = sequence(k * n);
+            assert_eq!(
+                multiply(&a, &b, m, k, n).unwrap(),
+                multiply_naive(&a, &b, m, k, n).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_f32_rectangular() {
+        // [1 2 3]   [1 0]   [4  5]
+        // [4 5 6] · [0 1] = [10 11]
+        //           [1 1]
+        let a = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let b = [1.0f32, 0.0, 0.0, 1.0, 1.0, 1.0];
+        assert_eq!(
+            multiply(&a, &b, 2, 3, 2).unwrap(),
+            vec![4.0, 5.0, 10.0, 11.0]
+        );
+    }
+
+    #[test]
+    fn test_shape_errors() {
+        let a = [1.0f64; 6];
+        assert_eq!(
+            multiply(&a, &a, 2, 3, 3),
+            Err(ComputeError::DimensionMismatch {
+                what: "b",
+                expected: 9,
+                actual: 6
+            })
+        );
+        assert!(multiply::<f64>(&[], &[], 0, 4, 0).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,424 @@
+//! Hash-based group-by and pivot over columnar data.
+//!
+//! Key columns are added to `GroupKeys`, which assigns every row a dense
+//! group id (groups numbered by first appearance). The resulting `Grouping`
+//! then aggregates any number of value columns without re-hashing the keys.
+//!
+//! NaN values are skipped by every aggregation, so `Count` is the number of
+//! non-NaN values in a group. Groups with no values get 0 for `Sum`, `Count`
+//! and `DistinctCount` and NaN for the rest.
+
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, try_filled, ComputeError, Result};
+use crate::matrix::Matrix;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Sum = 0,
+    Mean = 1,
+    Count = 2,
+    Min = 3,
+    Max = 4,
+    Median = 5,
+    /// Sample standard deviation (n - 1 denominator)
+    StdDev = 6,
+    DistinctCount = 7,
+}
+
+#[derive(Debug, Clone)]
+enum KeyColumn {
+    Numeric(Vec<f64>),
+    Text(Vec<String>),
+}
+
+/// Hashable identity of a float: -0.0 joins 0.0 and all NaNs form one group
+fn float_bits(x: f64) -> u64 {
+    if x.is_nan() {
+        f64::NAN.to_bits()
+    } else if x == 0.0 {
+        0
+    } else {
+        x.to_bits()
+    }
+}
+
+/// Key columns for a group-by
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct GroupKeys {
+    len: Option<usize>,
+    columns: Vec<KeyColumn>,
+}
+
+impl GroupKeys {
+    fn push(&mut self, len: usize, column: KeyColumn) -> Result<()> {
+        match self.len {
+            Some(expected) => check_len("group key column", len, expected)?,
+            None if len > u32::MAX as usize => {
+                return Err(ComputeError::Overflow("row count exceeds u32 group ids"))
+            }
+            None => self.len = Some(len),
+        }
+        self.columns.push(column);
+        Ok(())
+    }
+
+    pub fn push_numeric(&mut self, values: Vec<f64>) -> Result<()> {
+        self.push(values.len(), KeyColumn::Numeric(values))
+    }
+
+    pub fn push_text(&mut self, values: Vec<String>) -> Result<()> {
+        self.push(values.len(), KeyColumn::Text(values))
+    }
+
+    /// Assign group ids; consumes the keys so the columns move into the result
+    pub fn grouping(self) -> Result<Grouping> {
+        if self.columns.is_empty() {
+            return Err(ComputeError::invalid(
+                "group-by needs at least one key column",
+            ));
+        }
+        let rows = self.len.unwrap_or(0);
+
+        // Dictionary-encode string columns so composite keys are plain integers
+        let encoded: Vec<Vec<u64>> = self
+            .columns
+            .iter()
+            .map(|column| match column {
+                KeyColumn::Numeric(v) => v.iter().map(|&x| float_bits(x)).collect(),
+                KeyColumn::Text(v) => {
+                    let mut dict: HashMap<&str, u64> = HashMap::new();
+                    v.iter()
+                        .map(|s| {
+                            let next = dict.len() as u64;
+                            *dict.entry(s.as_str()).or_insert(next)
+                        })
+                        .collect()
+                }
+            })
+            .collect();
+
+        let mut ids = try_filled(rows, 0u32)?;
+        let mut first_row = Vec::new();
+        let mut lookup: HashMap<Vec<u64>, u32> = HashMap::new();
+        for (row, id) in ids.iter_mut().enumerate() {
+            let key: Vec<u64> = encoded.iter().map(|c| c[row]).collect();
+            let next = first_row.len() as u32;
+            *id = *lookup.entry(key).or_insert_with(|| {
+                first_row.push(row);
+                next
+            });
+        }
+
+        Ok(Grouping {
+            ids,
+            first_row,
+            columns: self.columns,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl GroupKeys {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GroupKeys {
+        GroupKeys::default()
+    }
+
+    #[wasm_bindgen(js_name = addNumeric)]
+    pub fn add_numeric(&mut self, values: Vec<f64>) -> Result<(), JsError> {
+        Ok(self.push_numeric(values)?)
+    }
+
+    #[wasm_bindgen(js_name = addStrings)]
+    pub fn add_strings(&mut self, values: Vec<String>) -> Result<(), JsError> {
+        Ok(self.push_text(values)?)
+    }
+
+    /// Hash the key columns into groups. The keys object is consumed.
+    pub fn group(self) -> Result<Grouping, JsError> {
+        Ok(self.grouping()?)
+    }
+}
+
+/// Rows assigned to groups, ready to aggregate value columns
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Grouping {
+    /// Group id of every row
+    ids: Vec<u32>,
+    /// First row of each group, used to read back the key values
+    first_row: Vec<usize>,
+    columns: Vec<KeyColumn>,
+}
+
+/// Aggregate `values` into `groups` buckets according to `ids`
+fn aggregate_ids(ids: &[u32], groups: usize, values: &[f64], op: Aggregation) -> Result<Vec<f64>> {
+    check_len("value column", values.len(), ids.len())?;
+
+    // Counting sort of the non-NaN values by group
+    let mut offsets = try_filled(groups + 1, 0usize)?;
+    for (&id, v) in ids.iter().zip(values) {
+        if !v.is_nan() {
+            offsets[id as usize + 1] += 1;
+        }
+    }
+    for g in 0..groups {
+        offsets[g + 1] += offsets[g];
+    }
+    let mut next = offsets.clone();
+    let mut buckets = try_filled(offsets[groups], 0.0)?;
+    for (&id, &v) in ids.iter().zip(values) {
+        if !v.is_nan() {
+            buckets[next[id as usize]] = v;
+            next[id as usize] += 1;
+        }
+    }
+
+    let mut out = try_filled(groups, 0.0)?;
+    for (g, result) in out.iter_mut().enumerate() {
+        *result = reduce(&mut buckets[offsets[g]..offsets[g + 1]], op);
+    }
+    Ok(out)
+}
+
+/// Reduce one group's (NaN-free) values; may reorder them
+fn reduce(v: &mut [f64], op: Aggregation) -> f64 {
+    let n = v.len() as f64;
+    match op {
+        Aggregation::Count => n,
+        Aggregation::Sum => v.iter().sum(),
+        _ if v.is_empty() => match op {
+            Aggregation::DistinctCount => 0.0,
+            _ => f64::NAN,
+        },
+        Aggregation::Mean => v.iter().sum::<f64>() / n,
+        Aggregation::Min => v.iter().copied().fold(f64::INFINITY, f64::min),
+        Aggregation::Max => v.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        Aggregation::Median => {
+            let odd = v.len() % 2 == 1;
+            let (lower, &mut m, _) = v.select_nth_unstable_by(v.len() / 2, f64::total_cmp);
+            if odd {
+                m
+            } else {
+                let below = lower.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (below + m) / 2.0
+            }
+        }
+        Aggregation::StdDev => {
+            if v.len() < 2 {
+                return f64::NAN;
+            }
+            let mean = v.iter().sum::<f64>() / n;
+            let ss: f64 = v.iter().map(|x| (x - mean) * (x - mean)).sum();
+            (ss / (n - 1.0)).sqrt()
+        }
+        Aggregation::DistinctCount => {
+            v.sort_unstable_by(f64::total_cmp);
+            1.0 + v.windows(2).filter(|w| w[0] != w[1]).count() as f64
+        }
+    }
+}
+
+impl Grouping {
+    pub fn len(&self) -> usize {
+        self.first_row.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.first_row.is_empty()
+    }
+
+    pub fn group_ids(&self) -> &[u32] {
+        &self.ids
+    }
+
+    fn column(&self, index: usize) -> Result<&KeyColumn> {
+        self.columns.get(index).ok_or_else(|| {
+            ComputeError::invalid(format!(
+                "key column {index} out of range ({} columns)",
+                self.columns.len()
+            ))
+        })
+    }
+
+    pub fn aggregate_values(&self, values: &[f64], op: Aggregation) -> Result<Vec<f64>> {
+        aggregate_ids(&self.ids, self.len(), values, op)
+    }
+
+    /// Cell index (`row group * column groups + column group`) of every row
+    fn cell_ids(&self, columns: &Grouping) -> Result<Vec<u32>> {
+        check_len("pivot column keys", columns.ids.len(), self.ids.len())?;
+        let cols = columns.len();
+        self.ids
+            .iter()
+            .zip(&columns.ids)
+            .map(|(&r, &c)| u32::try_from(r as usize * cols + c as usize))
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| ComputeError::Overflow("pivot table exceeds u32 cells"))
+    }
+
+    /// `self` groups become rows, `columns` groups become columns; empty
+    /// cells hold NaN (0 for `Count`, `Sum` and `DistinctCount`)
+    pub fn pivot_values(
+        &self,
+        columns: &Grouping,
+        values: &[f64],
+        op: Aggregation,
+    ) -> Result<Matrix> {
+        let (rows, cols) = (self.len(), columns.len());
+        let cells = crate::error::checked_area(rows, cols)?;
+        let cell_ids = self.cell_ids(columns)?;
+        Matrix::from_vec(aggregate_ids(&cell_ids, cells, values, op)?, rows, cols)
+    }
+
+    /// Number of input rows in each pivot cell, NaN values or not; the pivot
+    /// counterpart of `sizes`
+    pub fn pivot_cell_sizes(&self, columns: &Grouping) -> Result<Matrix> {
+        let (rows, cols) = (self.len(), columns.len());
+        let mut sizes = try_filled(crate::error::checked_area(rows, cols)?, 0.0)?;
+        for id in self.cell_ids(columns)? {
+            sizes[id as usize] += 1.0;
+        }
+        Matrix::from_vec(sizes, rows, cols)
+    }
+}
+
+#[wasm_bindgen]
+impl Grouping {
+    #[wasm_bindgen(getter, js_name = groupCount)]
+    pub fn group_count(&self) -> usize {
+        self.len()
+    }
+
+    /// Group id of every input row
+    #[wasm_bindgen(js_name = groupIds)]
+    pub fn js_group_ids(&self) -> Vec<u32> {
+        self.ids.clone()
+    }
+
+    /// Number of input rows in each group
+    pub fn sizes(&self) -> Vec<u32> {
+        let mut sizes = vec![0u32; self.len()];
+        self.ids.iter().for_each(|&id| sizes[id as usize] += 1);
+        sizes
+    }
+
+    /// Per-group value of numeric key column `index`
+    #[wasm_bindgen(js_name = numericKey)]
+    pub fn numeric_key(&self, index: usize) -> Result<Vec<f64>, JsError> {
+        match self.column(index)? {
+            KeyColumn::Numeric(v) => Ok(self.first_row.iter().map(|&r| v[r]).collect()),
+            KeyColumn::Text(_) => {
+                Err(ComputeError::invalid(format!("key column {index} is not numeric")).into())
+            }
+        }
+    }
+
+    /// Per-group value of string key column `index`
+    #[wasm_bindgen(js_name = stringKey)]
+    pub fn string_key(&self, index: usize) -> Result<Vec<String>, JsError> {
+        match self.column(index)? {
+            KeyColumn::Text(v) => Ok(self.first_row.iter().map(|&r| v[r].clone()).collect()),
+            KeyColumn::Numeric(_) => Err(ComputeError::invalid(format!(
+                "key column {index} is not a string column"
+            ))
+            .into()),
+        }
+    }
+
+    /// Aggregate a value column (one entry per input row) into one value per group
+    pub fn aggregate(&self, values: &[f64], op: Aggregation) -> Result<Vec<f64>, JsError> {
+        Ok(self.aggregate_values(values, op)?)
+    }
+
+    /// Pivot table: rows are this grouping's groups, columns are `columns`' groups
+    pub fn pivot(
+        &self,
+        columns: &Grouping,
+        values: &[f64],
+        op: Aggregation,
+    ) -> Result<Matrix, JsError> {
+        Ok(self.pivot_values(columns, values, op)?)
+    }
+
+    /// Rows per pivot cell, shaped like `pivot`'s result
+    #[wasm_bindgen(js_name = pivotSizes)]
+    pub fn pivot_sizes(&self, columns: &Grouping) -> Result<Matrix, JsError> {
+        Ok(self.pivot_cell_sizes(columns)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grouping(keys: &[&str]) -> Grouping {
+        let mut g = GroupKeys::new();
+        g.push_text(keys.iter().map(|s| s.to_string()).collect())
+            .unwrap();
+        g.grouping().unwrap()
+    }
+
+    #[test]
+    fn test_group_and_aggregate() {
+        let g = grouping(&["a", "b", "a", "c", "a", "b"]);
+        assert_eq!(g.group_ids(), &[0, 1, 0, 2, 0, 1]);
+        let values = [1.0, 10.0, 3.0, f64::NAN, 2.0, 10.0];
+        let agg = |op| g.aggregate_values(&values, op).unwrap();
+
+        assert_eq!(agg(Aggregation::Sum), vec![6.0, 20.0, 0.0]);
+        assert_eq!(agg(Aggregation::Count), vec![3.0, 2.0, 0.0]);
+        assert_eq!(agg(Aggregation::Mean)[..2], [2.0, 10.0]);
+        assert!(agg(Aggregation::Mean)[2].is_nan());
+        assert_eq!(agg(Aggregation::Min)[..2], [1.0, 10.0]);
+        assert_eq!(agg(Aggregation::Max)[..2], [3.0, 10.0]);
+        assert_eq!(agg(Aggregation::Median)[..2], [2.0, 10.0]);
+        assert_eq!(agg(Aggregation::StdDev)[..2], [1.0, 0.0]);
+        assert_eq!(agg(Aggregation::DistinctCount), vec![3.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_composite_numeric_keys() {
+        let mut keys = GroupKeys::new();
+        keys.push_numeric(vec![1.0, 1.0, 2.0, -0.0, 0.0]).unwrap();
+        keys.push_text(["x", "y", "x", "z", "z"].map(String::from).to_vec())
+            .unwrap();
+        let g = keys.grouping().unwrap();
+        // -0.0 and 0.0 share a group
+        assert_eq!(g.group_ids(), &[0, 1, 2, 3, 3]);
+        assert_eq!(
+            g.aggregate_values(&[1.0, 2.0, 3.0, 4.0, 6.0], Aggregation::Median)
+                .unwrap()[3],
+            5.0
+        );
+    }
+
+    #[test]
+    fn test_pivot() {
+        let rows = grouping(&["r1", "r1", "r2", "r2"]);
+        let cols = grouping(&["c1", "c2", "c1", "c1"]);
+        let table = rows
+            .pivot_values(&cols, &[1.0, 2.0, 3.0, 4.0], Aggregation::Sum)
+            .unwrap();
+        assert_eq!((table.rows(), table.cols()), (2, 2));
+        assert_eq!(table.data(), &[1.0, 2.0, 7.0, 0.0]);
+        assert!(rows
+            .pivot_values(&grouping(&["c1"]), &[1.0], Aggregation::Sum)
+            .is_err());
+        assert!(GroupKeys::new().grouping().is_err());
+
+        // Count skips NaN values; cell sizes count every row
+        let values = [1.0, f64::NAN, 3.0, f64::NAN];
+        let counts = rows
+            .pivot_values(&cols, &values, Aggregation::Count)
+            .unwrap();
+        assert_eq!(counts.data(), &[1.0, 0.0, 1.0, 0.0]);
+        let sizes = rows.pivot_cell_sizes(&cols).unwrap();
+        assert_eq!(sizes.data(), &[1.0, 1.0, 2.0, 0.0]);
+    }
+}
@@ -0,0 +1,360 @@
+//! Histograms, equalization and auto-levels for RGBA buffers.
+//!
+//! Luminance is Rec. 601 luma of the sRGB bytes (the weights `grayscale`
+//! uses). Equalization remaps luma only and shifts R, G and B by the same
+//! amount, i.e. it changes Y in full-range YCbCr and keeps Cb/Cr, so hues
+//! are not disturbed. Alpha is never modified.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{try_filled, ComputeError, Result};
+use crate::image::{check_image, check_rgba, to_u8};
+
+/// Most CLAHE tiles per axis; each tile costs about 2 KiB of tables
+const MAX_TILES: u32 = 256;
+
+/// Rec. 601 luma, rounded, in 8.8 fixed point
+fn luma(px: &[u8]) -> u8 {
+    ((77 * px[0] as u32 + 150 * px[1] as u32 + 29 * px[2] as u32 + 128) >> 8) as u8
+}
+
+/// Shift the RGB of `px` so its luma moves from `from` to `to`
+fn shift_luma(px: &mut [u8], from: u8, to: f32) {
+    let d = to - from as f32;
+    for c in &mut px[..3] {
+        *c = to_u8(*c as f32 + d);
+    }
+}
+
+/// Equalizing map: each value to its rank in the cumulative distribution
+fn equalize_map(hist: &[u32; 256]) -> [u8; 256] {
+    let total: u64 = hist.iter().map(|&n| u64::from(n)).sum();
+    let first = hist
+        .iter()
+        .position(|&n| n > 0)
+        .map_or(0, |v| hist[v] as u64);
+    let mut map: [u8; 256] = std::array::from_fn(|v| v as u8);
+    if total == first {
+        // Empty or single-valued: nothing to spread
+        return map;
+    }
+    let mut cdf = 0u64;
+    for (v, &n) in hist.iter().enumerate() {
+        cdf += u64::from(n);
+        if n > 0 {
+            map[v] = ((cdf - first) as f64 * 255.0 / (total - first) as f64).round() as u8;
+        }
+    }
+    map
+}
+
+/// Per-channel and luminance counts, 256 bins each
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    channels: [[u32; 256]; 4],
+    luminance: [u32; 256],
+}
+
+impl Histogram {
+    pub fn compute(data: &[u8]) -> Result<Histogram> {
+        check_rgba(data)?;
+        let mut channels = [[0u32; 256]; 4];
+        let mut luminance = [0u32; 256];
+        for px in data.chunks_exact(4) {
+            for (bins, &v) in channels.iter_mut().zip(px) {
+                bins[v as usize] += 1;
+            }
+            luminance[luma(px) as usize] += 1;
+        }
+        Ok(Histogram {
+            channels,
+            luminance,
+        })
+    }
+
+    /// Counts for channel `i` (0 red, 1 green, 2 blue, 3 alpha)
+    pub fn channel(&self, i: usize) -> &[u32; 256] {
+        &self.channels[i]
+    }
+
+    pub fn luma(&self) -> &[u32; 256] {
+        &self.luminance
+    }
+}
+
+#[wasm_bindgen]
+impl Histogram {
+    #[wasm_bindgen(getter)]
+    pub fn red(&self) -> Vec<u32> {
+        self.channels[0].to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn green(&self) -> Vec<u32> {
+        self.channels[1].to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn blue(&self) -> Vec<u32> {
+        self.channels[2].to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn alpha(&self) -> Vec<u32> {
+        self.channels[3].to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn luminance(&self) -> Vec<u32> {
+        self.luminance.to_vec()
+    }
+}
+
+/// Global histogram equalization of luma
+pub fn equalize_rgba(data: &mut [u8]) -> Result<()> {
+    let map = equalize_map(Histogram::compute(data)?.luma());
+    for px in data.chunks_exact_mut(4) {
+        let y = luma(px);
+        shift_luma(px, y, map[y as usize] as f32);
+    }
+    Ok(())
+}
+
+/// Contrast-limited adaptive histogram equalization
+///
+/// The image is split into a `tiles_x × tiles_y` grid, each tile's luma
+/// histogram is clipped at `clip_limit` times the mean bin count (excess
+/// spread over all bins) and equalized, and every pixel blends the maps of
+/// its four nearest tile centres bilinearly. At most `MAX_TILES` tiles fit
+/// along each axis.
+pub fn clahe_rgba(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    tiles_x: u32,
+    tiles_y: u32,
+    clip_limit: f32,
+) -> Result<()> {
+    check_image(data, width, height)?;
+    let fits = |tiles: u32, len: u32| (1..=len.min(MAX_TILES)).contains(&tiles);
+    if !fits(tiles_x, width) || !fits(tiles_y, height) {
+        return Err(ComputeError::invalid(format!(
+            "{tiles_x}×{tiles_y} tiles do not fit a {width}×{height} image \
+             (at most {MAX_TILES} per axis)"
+        )));
+    }
+    if !(clip_limit.is_finite() && clip_limit >= 1.0) {
+        return Err(ComputeError::invalid(format!(
+            "clip limit must be at least 1, got {clip_limit}"
+        )));
+    }
+    let (w, h) = (width as usize, height as usize);
+    let (nx, ny) = (tiles_x as usize, tiles_y as usize);
+    let tile_of = |i: usize, n: usize, len: usize| (i as u64 * n as u64 / len as u64) as usize;
+
+    let mut hists = try_filled(nx * ny, [0u32; 256])?;
+    for (y, row) in data.chunks_exact(w * 4).enumerate() {
+        let ty = tile_of(y, ny, h);
+        for (x, px) in row.chunks_exact(4).enumerate() {
+            hists[ty * nx + tile_of(x, nx, w)][luma(px) as usize] += 1;
+        }
+    }
+
+    let mut maps = try_filled(nx * ny, [0.0f32; 256])?;
+    for (map, hist) in maps.iter_mut().zip(&mut hists) {
+        let pixels: u32 = hist.iter().sum();
+        let limit = ((clip_limit * pixels as f32 / 256.0) as u32).max(1);
+        let mut excess = 0;
+        for n in hist.iter_mut() {
+            excess += n.saturating_sub(limit);
+            *n = (*n).min(limit);
+        }
+        let (share, rest) = (excess / 256, excess % 256);
+        let mut cdf = 0;
+        *map = std::array::from_fn(|v| {
+            cdf += hist[v] + share + u32::from((v as u32) < rest);
+            cdf as f32 * 255.0 / pixels as f32
+        });
+    }
+
+    // Fractional tile coordinate of a pixel centre: lower tile and weight
+    let locate = |i: usize, n: usize, len: usize| {
+        let f = ((i as f32 + 0.5) * n as f32 / len as f32 - 0.5).max(0.0);
+        let t0 = (f as usize).min(n - 1);
+        (t0, (t0 + 1).min(n - 1), f - t0 as f32)
+    };
+    let columns: Vec<_> = (0..w).map(|x| locate(x, nx, w)).collect();
+    for (y, row) in data.chunks_exact_mut(w * 4).enumerate() {
+        let (y0, y1, wy) = locate(y, ny, h);
+        for (px, &(x0, x1, wx)) in row.chunks_exact_mut(4).zip(&columns) {
+            let v = luma(px);
+            let at = |ty: usize, tx: usize| maps[ty * nx + tx][v as usize];
+            let top = at(y0, x0) * (1.0 - wx) + at(y0, x1) * wx;
+            let bottom = at(y1, x0) * (1.0 - wx) + at(y1, x1) * wx;
+            shift_luma(px, v, top * (1.0 - wy) + bottom * wy);
+        }
+    }
+    Ok(())
+}
+
+/// First value in `order` at which the running count of `bins` exceeds `cut`
+fn first_past(
+    bins: &[u32; 256],
+    mut order: impl Iterator<Item = usize>,
+    cut: u64,
+) -> Option<usize> {
+    let mut seen = 0u64;
+    order.find(|&v| {
+        seen += u64::from(bins[v]);
+        seen > cut
+    })
+}
+
+/// Stretch each of R, G and B to the full `0..=255` range, ignoring the
+/// darkest and brightest `clip` fraction of pixels (e.g. `0.005`)
+pub fn auto_levels_rgba(data: &mut [u8], clip: f32) -> Result<()> {
+    if !(0.0..0.5).contains(&clip) {
+        return Err(ComputeError::invalid(format!(
+            "clip fraction must be in [0, 0.5), got {clip}"
+        )));
+    }
+    let hist = Histogram::compute(data)?;
+    let cut = (clip as f64 * (data.len() / 4) as f64) as u64;
+    let mut maps = [[0u8; 256]; 3];
+    for (c, map) in maps.iter_mut().enumerate() {
+        let bins = hist.channel(c);
+        let lo = first_past(bins, 0..256, cut).unwrap_or(0);
+        let hi = first_past(bins, (0..256).rev(), cut).unwrap_or(255);
+        *map = std::array::from_fn(|v| {
+            if hi <= lo {
+                v as u8
+            } else {
+                to_u8((v as f32 - lo as f32) * 255.0 / (hi - lo) as f32)
+            }
+        });
+    }
+    for px in data.chunks_exact_mut(4) {
+        for (v, map) in px.iter_mut().zip(&maps) {
+            *v = map[*v as usize];
+        }
+    }
+    Ok(())
+}
+
+/// Per-channel (`red`, `green`, `blue`, `alpha`) and `luminance` histograms
+#[wasm_bindgen]
+pub fn histogram(data: &[u8]) -> Result<Histogram, JsError> {
+    Ok(Histogram::compute(data)?)
+}
+
+/// Global luma histogram equalization, in place
+#[wasm_bindgen]
+pub fn equalize_histogram(data: &mut [u8]) -> Result<(), JsError> {
+    Ok(equalize_rgba(data)?)
+}
+
+/// CLAHE over a `tilesX × tilesY` grid (at most 256 per axis); `clipLimit`
+/// around 2–4 is typical
+#[wasm_bindgen]
+pub fn clahe(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    tiles_x: u32,
+    tiles_y: u32,
+    clip_limit: f32,
+) -> Result<(), JsError> {
+    Ok(clahe_rgba(
+        data, width, height, tiles_x, tiles_y, clip_limit,
+    )?)
+}
+
+/// Per-channel contrast stretch, clipping the `clip` fraction at each end
+#[wasm_bindgen]
+pub fn auto_levels(data: &mut [u8], clip: f32) -> Result<(), JsError> {
+    Ok(auto_levels_rgba(data, clip)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grey(values: &[u8]) -> Vec<u8> {
+        values.iter().flat_map(|&v| [v, v, v, 200]).collect()
+    }
+
+    #[test]
+    fn test_histogram() {
+        let data = [255, 0, 0, 255, 0, 0, 255, 0, 10, 10, 10, 255];
+        let h = Histogram::compute(&data).unwrap();
+        assert_eq!((h.red()[255], h.red()[0], h.red()[10]), (1, 1, 1));
+        assert_eq!((h.alpha()[255], h.alpha()[0]), (2, 1));
+        // Rec. 601 luma of pure red, pure blue and grey 10
+        assert_eq!((h.luma()[77], h.luma()[29], h.luma()[10]), (1, 1, 1));
+        assert_eq!(h.luminance().iter().sum::<u32>(), 3);
+        assert!(Histogram::compute(&[0; 5]).is_err());
+    }
+
+    #[test]
+    fn test_equalize_and_auto_levels() {
+        let mut data = grey(&[100, 100, 110, 120, 120, 120]);
+        equalize_rgba(&mut data).unwrap();
+        assert_eq!(data, grey(&[0, 0, 64, 255, 255, 255]));
+
+        let mut flat = grey(&[90; 4]);
+        equalize_rgba(&mut flat).unwrap();
+        assert_eq!(flat, grey(&[90; 4]));
+
+        let mut data: Vec<u8> = [[60, 10, 0, 1], [80, 30, 5, 2], [100, 50, 10, 3]].concat();
+        auto_levels_rgba(&mut data, 0.0).unwrap();
+        assert_eq!(
+            data,
+            [[0, 0, 0, 1], [128, 128, 128, 2], [255, 255, 255, 3]].concat()
+        );
+        assert!(auto_levels_rgba(&mut data, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_clahe() {
+        // Dark low-contrast left half, bright low-contrast right half
+        let (w, h) = (64u32, 32u32);
+        let image: Vec<u8> = (0..w * h)
+            .flat_map(|i| {
+                let (x, y) = (i % w, i / w);
+                let v = if x < w / 2 { 20 } else { 220 } + ((x + y) % 4 * 3) as u8;
+                [v, v, v, 255]
+            })
+            .collect();
+        let spread = |d: &[u8], range: std::ops::Range<u32>| {
+            let vals: Vec<u8> = (0..w * h)
+                .filter(|i| range.contains(&(i % w)))
+                .map(|i| d[i as usize * 4])
+                .collect();
+            vals.iter().max().unwrap() - vals.iter().min().unwrap()
+        };
+        let before = spread(&image, 0..16);
+        let run = |clip_limit| {
+            let mut data = image.clone();
+            clahe_rgba(&mut data, w, h, 2, 1, clip_limit).unwrap();
+            data
+        };
+        let strong = run(40.0);
+        assert!(spread(&strong, 0..16) > 10 * before);
+        assert!(spread(&strong, 48..64) > 10 * before);
+        assert!(strong.chunks(4).all(|p| p[3] == 255));
+        // A low clip limit bounds the contrast gain
+        let mild = run(2.0);
+        assert!(spread(&mild, 0..16) < spread(&strong, 0..16) / 2);
+
+        let mut data = image.clone();
+        assert!(clahe_rgba(&mut data, w, h, 0, 1, 2.0).is_err());
+        assert!(clahe_rgba(&mut data, w, h, 4, 33, 2.0).is_err());
+        assert!(clahe_rgba(&mut data, w, h, 4, 4, 0.5).is_err());
+        // Tile grids are capped even when the image could hold more
+        let mut wide = [128u8; 4 * 4096];
+        assert!(clahe_rgba(&mut wide, 4096, 1, MAX_TILES, 1, 2.0).is_ok());
+        assert!(clahe_rgba(&mut wide, 4096, 1, MAX_TILES + 1, 1, 2.0).is_err());
+        assert!(clahe_rgba(&mut wide, 4096, 1, 4096, 1, 2.0).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! Shared plumbing for the RGBA image kernels.
+//!
+//! Buffers are canvas `ImageData` layout: row-major, 4 bytes per pixel,
+//! straight (non-premultiplied) alpha. Kernels that mix neighbouring pixels
+//! work on `f32` copies with premultiplied alpha, so fully transparent
+//! pixels do not bleed their (invisible) colour into the result.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, checked_area, try_filled, ComputeError, Result};
+
+/// How pixels outside the image are sampled
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeMode {
+    /// Repeat the nearest edge pixel
+    #[default]
+    Clamp = 0,
+    /// Tile the image
+    Wrap = 1,
+    /// Reflect about the edge pixel (`c b | a b c | b a`)
+    Mirror = 2,
+    /// Treat outside pixels as transparent black
+    Zero = 3,
+}
+
+impl EdgeMode {
+    /// Source index for coordinate `i` on an axis of length `n`, `None` for
+    /// `Zero` and for an empty axis
+    pub fn resolve(self, i: isize, n: usize) -> Option<usize> {
+        let n = n as isize;
+        if n == 0 {
+            return None;
+        }
+        if (0..n).contains(&i) {
+            return Some(i as usize);
+        }
+        match self {
+            EdgeMode::Clamp => Some(i.clamp(0, n - 1) as usize),
+            EdgeMode::Wrap => Some(i.rem_euclid(n) as usize),
+            EdgeMode::Mirror if n == 1 => Some(0),
+            EdgeMode::Mirror => {
+                let period = 2 * (n - 1);
+                let j = i.rem_euclid(period);
+                Some((if j < n { j } else { period - j }) as usize)
+            }
+            EdgeMode::Zero => None,
+        }
+    }
+
+    /// `resolve` for every coordinate in `-before..n + after`
+    pub fn table(self, n: usize, before: usize, after: usize) -> Result<Vec<Option<usize>>> {
+        let len = n
+            .checked_add(before)
+            .and_then(|l| l.checked_add(after))
+            .filter(|&l| l <= isize::MAX as usize)
+            .ok_or(ComputeError::Overflow(
+                "edge table exceeds the address space",
+            ))?;
+        let mut table = try_filled(len, None)?;
+        for (slot, i) in table.iter_mut().zip(-(before as isize)..) {
+            *slot = self.resolve(i, n);
+        }
+        Ok(table)
+    }
+}
+
+/// RGBA pixels with their dimensions, for kernels that change the image size
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(pixels: Vec<u8>, width: u32, height: u32) -> Result<RgbaImage> {
+        check_image(&pixels, width, height)?;
+        Ok(RgbaImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+}
+
+#[wasm_bindgen]
+impl RgbaImage {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// `width × height × 4` bytes, ready for `new ImageData(...)`
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.pixels.clone()
+    }
+}
+
+/// RGBA buffers must hold whole pixels
+pub(crate) fn check_rgba(data: &[u8]) -> Result<()> {
+    check_len("RGBA buffer", data.len(), data.len() / 4 * 4)
+}
+
+/// Check that `data` holds `width × height` RGBA pixels; returns the pixel count
+pub(crate) fn check_image(data: &[u8], width: u32, height: u32) -> Result<usize> {
+    let pixels = checked_area(width as usize, height as usize)?;
+    let bytes = pixels
+        .checked_mul(4)
+        .ok_or(ComputeError::Overflow("image dimensions"))?;
+    check_len("RGBA buffer", data.len(), bytes)?;
+    Ok(pixels)
+}
+
+/// Byte value of a float channel, rounded and saturated
+pub(crate) fn to_u8(x: f32) -> u8 {
+    x.round().clamp(0.0, 255.0) as u8
+}
+
+/// RGBA as `f32` in `0..=255` with colour channels multiplied by alpha
+pub(crate) fn premultiplied(data: &[u8]) -> Vec<f32> {
+    let mut out = Vec::with_capacity(data.len());
+    for px in data.chunks_exact(4) {
+        let a = px[3] as f32 / 255.0;
+        out.extend_from_slice(&[
+            px[0] as f32 * a,
+            px[1] as f32 * a,
+            px[2] as f32 * a,
+            px[3] as f32,
+        ]);
+    }
+    out
+}
+
+/// Inverse of [`premultiplied`], writing straight-alpha bytes into `out`
+pub(crate) fn store_premultiplied(px: &[f32], out: &mut [u8]) {
+    for (src, dst) in px.chunks_exact(4).zip(out.chunks_exact_mut(4)) {
+        let a = src[3].clamp(0.0, 255.0);
+        let scale = if a > 0.0 { 255.0 / a } else { 0.0 };
+        dst[0] = to_u8(src[0] * scale);
+        dst[1] = to_u8(src[1] * scale);
+        dst[2] = to_u8(src[2] * scale);
+        dst[3] = to_u8(a);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_modes() {
+        let idx = |mode: EdgeMode| -> Vec<Option<usize>> { mode.table(3, 2, 2).unwrap() };
+        let some = |v: &[usize]| v.iter().map(|&i| Some(i)).collect::<Vec<_>>();
+        assert_eq!(idx(EdgeMode::Clamp), some(&[0, 0, 0, 1, 2, 2, 2]));
+        assert_eq!(idx(EdgeMode::Wrap), some(&[1, 2, 0, 1, 2, 0, 1]));
+        assert_eq!(idx(EdgeMode::Mirror), some(&[2, 1, 0, 1, 2, 1, 0]));
+        assert_eq!(
+            idx(EdgeMode::Zero),
+            vec![None, None, Some(0), Some(1), Some(2), None, None]
+        );
+        assert_eq!(EdgeMode::Mirror.resolve(-5, 1), Some(0));
+        for mode in [
+            EdgeMode::Clamp,
+            EdgeMode::Wrap,
+            EdgeMode::Mirror,
+            EdgeMode::Zero,
+        ] {
+            assert_eq!(mode.table(0, 1, 1).unwrap(), [None, None]);
+        }
+    }
+
+    #[test]
+    fn test_premultiplied_round_trip() {
+        let data = [200, 100, 50, 128, 10, 20, 30, 0, 1, 2, 3, 255];
+        let mut out = [0u8; 12];
+        store_premultiplied(&premultiplied(&data), &mut out);
+        // Colour of a fully transparent pixel is lost
+        assert_eq!(out, [200, 100, 50, 128, 0, 0, 0, 0, 1, 2, 3, 255]);
+        assert!(check_image(&data, 3, 1).is_ok());
+        assert!(check_image(&data, 2, 2).is_err());
+    }
+}
@@ -0,0 +1,425 @@
+//! JSON array of records to a columnar [`Table`].
+//!
+//! The raw UTF-8 bytes are parsed straight into column builders, so no
+//! intermediate JS or Rust object tree is created. Columns appear in the
+//! order their keys are first seen; records missing a key get a null.
+//! Nested objects and arrays are kept as their JSON text in a text column.
+
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ComputeError, Result};
+use crate::table::{Cell, ColumnBuilder, Table};
+
+/// Nesting limit for values skipped as raw JSON
+const MAX_DEPTH: usize = 256;
+
+struct Parser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+/// A parsed value that may borrow from the input
+enum Value<'a> {
+    Cell(Cell<'a>),
+    /// Text that needed unescaping
+    Owned(String),
+}
+
+impl<'a> Parser<'a> {
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> ComputeError {
+        let before = &self.bytes[..pos.min(self.bytes.len())];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        ComputeError::Parse {
+            line: before.iter().filter(|&&b| b == b'\n').count() + 1,
+            column: pos - line_start + 1,
+            message: message.into(),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ComputeError {
+        self.error_at(self.pos, message)
+    }
+
+    fn unexpected(&self, expected: &str) -> ComputeError {
+        match self.text[self.pos..].chars().next() {
+            Some(c) => self.error(format!("expected {expected}, found {c:?}")),
+            None => self.error(format!("expected {expected}, found end of input")),
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, b: u8) -> Result<()> {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("{:?}", b as char)))
+        }
+    }
+
+    fn literal(&mut self, word: &str) -> Result<()> {
+        if self.text[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(())
+        } else {
+            Err(self.error("invalid literal"))
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|d| d.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("checked hex digits"))
+    }
+
+    /// String starting at the opening quote; borrowed unless it has escapes
+    fn string(&mut self) -> Result<Value<'a>> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        let mut owned: Option<String> = None;
+        let mut run = start;
+        loop {
+            let Some(&b) = self.bytes.get(self.pos) else {
+                return Err(self.error_at(start - 1, "unterminated string"));
+            };
+            match b {
+                b'"' => {
+                    let text = &self.text[run..self.pos];
+                    self.pos += 1;
+                    return Ok(match owned {
+                        Some(mut s) => {
+                            s.push_str(text);
+                            Value::Owned(s)
+                        }
+                        None => Value::Cell(Cell::Text(text)),
+                    });
+                }
+                b'\\' => {
+                    owned
+                        .get_or_insert_with(String::new)
+                        .push_str(&self.text[run..self.pos]);
+                    let escape = self.bytes.get(self.pos + 1).copied();
+                    self.pos += 2;
+                    let c = match escape {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let hi = self.hex4()?;
+                            let code = if (0xD800..0xDC00).contains(&hi)
+                                && self.text[self.pos..].starts_with("\\u")
+                            {
+                                let next = self.pos;
+                                self.pos += 2;
+                                let lo = self.hex4()?;
+                                if (0xDC00..0xE000).contains(&lo) {
+                                    0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)
+                                } else {
+                                    // Not a pair: re-read the second escape alone
+                                    self.pos = next;
+                                    hi
+                                }
+                            } else {
+                                hi
+                            };
+                            // Lone surrogates become U+FFFD, as in `TextDecoder`
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(self.error_at(self.pos - 2, "invalid escape")),
+                    };
+                    owned.get_or_insert_with(String::new).push(c);
+                    run = self.pos;
+                }
+                0x00..=0x1F => return Err(self.error("control character in string")),
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Cell<'a>> {
+        let start = self.pos;
+        let digits = |p: &mut Parser| {
+            let from = p.pos;
+            while p.bytes.get(p.pos).is_some_and(u8::is_ascii_digit) {
+                p.pos += 1;
+            }
+            p.pos - from
+        };
+        if self.bytes.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        let int_start = self.pos;
+        let int_digits = digits(self);
+        if int_digits == 0 || (int_digits > 1 && self.bytes[int_start] == b'0') {
+            return Err(self.error_at(start, "invalid number"));
+        }
+        let mut integral = true;
+        if self.bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            integral = false;
+            if digits(self) == 0 {
+                return Err(self.error("expected digits after decimal point"));
+            }
+        }
+        if let Some(b'e' | b'E') = self.bytes.get(self.pos) {
+            self.pos += 1;
+            integral = false;
+            if let Some(b'+' | b'-') = self.bytes.get(self.pos) {
+                self.pos += 1;
+            }
+            if digits(self) == 0 {
+                return Err(self.error("expected exponent digits"));
+            }
+        }
+        let text = &self.text[start..self.pos];
+        if integral {
+            if let Ok(x) = text.parse::<i64>() {
+                return Ok(Cell::Integer(x));
+            }
+        }
+        Ok(Cell::Float(text.parse().expect("validated JSON number")))
+    }
+
+    /// Skip a nested object or array, returning its JSON text
+    fn raw(&mut self, depth: usize) -> Result<&'a str> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        let start = self.pos;
+        let close = if self.bytes[self.pos] == b'{' {
+            b'}'
+        } else {
+            b']'
+        };
+        self.pos += 1;
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(&self.text[start..self.pos]);
+        }
+        loop {
+            if close == b'}' {
+                self.string()?;
+                self.expect(b':')?;
+            }
+            self.value(depth + 1)?;
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b) if b == close => {
+                    self.pos += 1;
+                    return Ok(&self.text[start..self.pos]);
+                }
+                _ => return Err(self.unexpected(&format!("',' or {:?}", close as char))),
+            }
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value<'a>> {
+        Ok(Value::Cell(match self.peek() {
+            Some(b'"') => return self.string(),
+            Some(b'{' | b'[') => Cell::Text(self.raw(depth)?),
+            Some(b't') => self.literal("true").map(|_| Cell::Boolean(true))?,
+            Some(b'f') => self.literal("false").map(|_| Cell::Boolean(false))?,
+            Some(b'n') => self.literal("null").map(|_| Cell::Null)?,
+            Some(b'-' | b'0'..=b'9') => self.number()?,
+            _ => return Err(self.unexpected("a value")),
+        }))
+    }
+}
+
+/// Parse `[{...}, {...}]` into one column per key
+pub fn read_json(bytes: &[u8]) -> Result<Table> {
+    let text = std::str::from_utf8(bytes).map_err(|e| {
+        let p = Parser {
+            text: "",
+            bytes,
+            pos: 0,
+        };
+        p.error_at(e.valid_up_to(), "invalid UTF-8")
+    })?;
+    let mut p = Parser {
+        text,
+        bytes,
+        pos: 0,
+    };
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    p.pos = bytes.len() - text.len();
+
+    let mut columns: Vec<ColumnBuilder> = Vec::new();
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+    // Row + 1 of the last value written to each column
+    let mut filled: Vec<usize> = Vec::new();
+
+    p.expect(b'[')?;
+    let mut rows = 0;
+    if p.peek() == Some(b']') {
+        p.pos += 1;
+    } else {
+        loop {
+            let record_start = p.pos;
+            if p.peek() != Some(b'{') {
+                return Err(p.unexpected("an object"));
+            }
+            p.pos += 1;
+            if p.peek() == Some(b'}') {
+                p.pos += 1;
+            } else {
+                loop {
+                    p.skip_ws();
+                    let key_pos = p.pos;
+                    let key = match p.string()? {
+                        Value::Cell(Cell::Text(k)) => k.to_owned(),
+                        Value::Owned(k) => k,
+                        Value::Cell(_) => unreachable!("strings parse to text"),
+                    };
+                    p.expect(b':')?;
+                    let i = match by_name.get(&key) {
+                        Some(&i) => i,
+                        None => {
+                            let mut column = ColumnBuilder::new(key.clone());
+                            for _ in 0..rows {
+                                column.push(Cell::Null)?;
+                            }
+                            columns.push(column);
+                            filled.push(0);
+                            by_name.insert(key.clone(), columns.len() - 1);
+                            columns.len() - 1
+                        }
+                    };
+                    if filled[i] == rows + 1 {
+                        return Err(p.error_at(key_pos, format!("duplicate key {key:?}")));
+                    }
+                    match p.value(0)? {
+                        Value::Cell(cell) => columns[i].push(cell)?,
+                        Value::Owned(s) => columns[i].push(Cell::Text(&s))?,
+                    }
+                    filled[i] = rows + 1;
+                    match p.peek() {
+                        Some(b',') => p.pos += 1,
+                        Some(b'}') => {
+                            p.pos += 1;
+                            break;
+                        }
+                        _ => return Err(p.unexpected("',' or '}'")),
+                    }
+                }
+            }
+            rows += 1;
+            for (column, f) in columns.iter_mut().zip(&mut filled) {
+                if *f != rows {
+                    column.push(Cell::Null)?;
+                    *f = rows;
+                }
+            }
+            if rows > u32::MAX as usize {
+                return Err(p.error_at(record_start, "record count exceeds u32 row ids"));
+            }
+            match p.peek() {
+                Some(b',') => p.pos += 1,
+                Some(b']') => {
+                    p.pos += 1;
+                    break;
+                }
+                _ => return Err(p.unexpected("',' or ']'")),
+            }
+        }
+    }
+    if p.peek().is_some() {
+        return Err(p.unexpected("end of input"));
+    }
+    Table::from_columns(columns.into_iter().map(ColumnBuilder::finish).collect())
+}
+
+/// Parse the UTF-8 bytes of a JSON array of objects into a `Table`
+#[wasm_bindgen]
+pub fn parse_json(bytes: &[u8]) -> Result<Table, JsError> {
+    Ok(read_json(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::{ColumnData, ColumnType};
+
+    #[test]
+    fn test_records_to_columns() {
+        let json = r#" [
+            {"id": 1, "name": "a\"bé", "score": 1.5, "tags": ["x", {"y": null}]},
+            {"name": "\ud83d\ude00", "id": 2, "extra": true},
+            {"id": 9007199254740993, "score": -2e3, "name": null}
+        ] "#
+        .as_bytes();
+        let table = read_json(json).unwrap();
+        assert_eq!(table.row_count(), 3);
+        let names: Vec<&str> = table.columns().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["id", "name", "score", "tags", "extra"]);
+
+        let c = table.columns();
+        assert_eq!(
+            c[0].data(),
+            &ColumnData::Integer(vec![1, 2, 9_007_199_254_740_993])
+        );
+        assert_eq!(c[1].text(0).as_deref(), Some("a\"bé"));
+        assert_eq!(c[1].text(1).as_deref(), Some("😀"));
+        assert_eq!(c[1].text(2), None);
+        assert_eq!(c[2].column_type(), ColumnType::Float);
+        assert_eq!(c[2].null_count(), 1);
+        assert_eq!(c[3].text(0).as_deref(), Some(r#"["x", {"y": null}]"#));
+        assert_eq!(c[4].validity(), &[false, true, false]);
+
+        assert_eq!(read_json(b"[]").unwrap(), Table::default());
+    }
+
+    #[test]
+    fn test_lone_surrogates() {
+        let json =
+            r#"[{"s": "\ud800\u0041"}, {"s": "\udc00x\ud83d"}, {"s": "\ud83d\ud83d\ude00"}]"#;
+        let table = read_json(json.as_bytes()).unwrap();
+        let texts: Vec<String> = (0..3).filter_map(|i| table.columns()[0].text(i)).collect();
+        assert_eq!(texts, ["\u{fffd}A", "\u{fffd}x\u{fffd}", "\u{fffd}😀"]);
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let position = |json: &str| match read_json(json.as_bytes()) {
+            Err(ComputeError::Parse { line, column, .. }) => (line, column),
+            other => panic!("expected a parse error, got {other:?}"),
+        };
+        assert_eq!(position("[\n  {\"a\": 1,}\n]"), (2, 11));
+        assert_eq!(position("[{\"a\": 01}]"), (1, 8));
+        assert_eq!(position("[{\"a\": 1, \"a\": 2}]"), (1, 11));
+        assert_eq!(position("[{\"a\": \"open}]"), (1, 8));
+        assert_eq!(position("{\"a\": 1}"), (1, 1));
+        assert_eq!(position("[1]"), (1, 2));
+        assert_eq!(position("[{}] x"), (1, 6));
+        assert_eq!(position("[{\"a\": tru}]"), (1, 8));
+        assert!(matches!(
+            read_json(b"[{\"a\": \"\xff\"}]"),
+            Err(ComputeError::Parse { column: 9, .. })
+        ));
+    }
+}
@@ -1,6 +1,41 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 
+pub mod argsort;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "codecs")]
+pub mod codec;
+pub mod color;
+pub mod csv;
+pub mod eigen;
+pub mod error;
+pub mod fibonacci;
+pub mod filter;
+pub mod fuzzy;
+pub mod gemm;
+pub mod groupby;
+pub mod histogram;
+pub mod image;
+pub mod json;
+pub mod linalg;
+pub mod matrix;
+pub mod number_theory;
+pub mod primes;
+pub mod resize;
+pub mod search;
+pub mod sort;
+pub mod sparse;
+pub mod stats;
+pub mod summation;
+pub mod table;
+pub mod transform;
+
+pub use error::ComputeError;
+pub use matrix::Matrix;
+use image::check_rgba;
+use summation::SumMethod;
+
 /// Initialize panic hook for better error messages
 #[wasm_bindgen(start)]
 pub fn main() {
@@ -15,57 +50,41 @@ extern "C" {
     fn log(s: &str);
 }
 
+#[allow(unused_macros)]
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
 /// Simple computation example
 #[wasm_bindgen]
-pub fn add(a: i32, b: i32) -> i32 {
-    a + b
+pub fn add(a: i32, b: i32) -> Result<i32, JsError> {
+    a.checked_add(b)
+        .ok_or_else(|| ComputeError::Overflow("i32 addition").into())
 }
 
 /// Heavy computation: Calculate Fibonacci number
+///
+/// Throws on overflow (n > 93); use `fibonacci_decimal` or
+/// `fibonacci_bigint` for exact results beyond that.
 #[wasm_bindgen]
-pub fn fibonacci(n: u32) -> u64 {
-    match n {
-        0 => 0,
-        1 => 1,
-        _ => {
-            let mut a = 0u64;
-            let mut b = 1u64;
-            for _ in 2..=n {
-                let temp = a + b;
-                a = b;
-                b = temp;
-            }
-            b
-        }
-    }
+pub fn fibonacci(n: u32) -> Result<u64, JsError> {
+    Ok(fibonacci::checked(n)?)
 }
 
 /// Process large array: Sum all elements
+///
+/// `method` selects the summation algorithm and defaults to pairwise.
 #[wasm_bindgen]
-pub fn sum_array(data: &[f64]) -> f64 {
-    data.iter().sum()
+pub fn sum_array(data: &[f64], method: Option<SumMethod>) -> Result<f64, JsError> {
+    Ok(summation::sum(data, method.unwrap_or_default())?)
 }
 
 /// Matrix multiplication (example of heavy computation)
+///
+/// Square `n×n` case of `gemm_f64`, which uses the blocked kernel.
 #[wasm_bindgen]
-pub fn matrix_multiply(a: Vec<f64>, b: Vec<f64>, n: usize) -> Vec<f64> {
-    let mut result = vec![0.0; n * n];
-    
-    for i in 0..n {
-        for j in 0..n {
-            let mut sum = 0.0;
-            for k in 0..n {
-                sum += a[i * n + k] * b[k * n + j];
-            }
-            result[i * n + j] = sum;
-        }
-    }
-    
-    result
+pub fn matrix_multiply(a: Vec<f64>, b: Vec<f64>, n: usize) -> Result<Vec<f64>, JsError> {
+    Ok(gemm::multiply(&a, &b, n, n, n)?)
 }
 
 /// Data structure for complex computations
@@ -78,7 +97,7 @@ pub struct ComputeResult {
 
 /// Complex computation with result object
 #[wasm_bindgen]
-pub fn compute_complex(iterations: u32) -> JsValue {
+pub fn compute_complex(iterations: u32) -> Result<JsValue, JsError> {
     let start = js_sys::Date::now();
     
     // Simulate complex computation
@@ -95,12 +114,13 @@ pub fn compute_complex(iterations: u32) -> JsValue {
         duration_ms: duration,
     };
     
-    serde_wasm_bindgen::to_value(&compute_result).unwrap()
+    Ok(serde_wasm_bindgen::to_value(&compute_result)?)
 }
 
 /// Image processing: Apply grayscale filter
 #[wasm_bindgen]
-pub fn grayscale(data: &mut [u8]) {
+pub fn grayscale(data: &mut [u8]) -> Result<(), JsError> {
+    check_rgba(data)?;
     for chunk in data.chunks_mut(4) {
         if chunk.len() == 4 {
             let r = chunk[0] as f64;
@@ -115,80 +135,40 @@ pub fn grayscale(data: &mut [u8]) {
             chunk[2] = gray;
         }
     }
+    Ok(())
 }
 
 /// Image processing: Adjust brightness
+///
+/// Scales linear light rather than the sRGB bytes, so `factor` 2 is one
+/// stop brighter and midtones keep their hue.
 #[wasm_bindgen]
-pub fn adjust_brightness(data: &mut [u8], factor: f64) {
-    for chunk in data.chunks_mut(4) {
-        if chunk.len() == 4 {
-            chunk[0] = ((chunk[0] as f64 * factor).min(255.0)) as u8;
-            chunk[1] = ((chunk[1] as f64 * factor).min(255.0)) as u8;
-            chunk[2] = ((chunk[2] as f64 * factor).min(255.0)) as u8;
-        }
-    }
+pub fn adjust_brightness(data: &mut [u8], factor: f64) -> Result<(), JsError> {
+    Ok(color::brightness_rgba(data, factor as f32)?)
 }
 
-/// Sort array using quicksort (fast implementation)
+/// Sort array in ascending order, NaNs last
+///
+/// Kept for existing callers; see `sort_f64` and friends for direction and
+/// NaN placement options.
 #[wasm_bindgen]
 pub fn quicksort(arr: &mut [f64]) {
-    if arr.len() <= 1 {
-        return;
-    }
-    quicksort_recursive(arr, 0, arr.len() - 1);
-}
-
-fn quicksort_recursive(arr: &mut [f64], low: usize, high: usize) {
-    if low < high {
-        let pi = partition(arr, low, high);
-        if pi > 0 {
-            quicksort_recursive(arr, low, pi - 1);
-        }
-        quicksort_recursive(arr, pi + 1, high);
-    }
-}
-
-fn partition(arr: &mut [f64], low: usize, high: usize) -> usize {
-    let pivot = arr[high];
-    let mut i = low;
-    
-    for j in low..high {
-        if arr[j] < pivot {
-            arr.swap(i, j);
-            i += 1;
-        }
-    }
-    
-    arr.swap(i, high);
-    i
+    sort::sort(arr, sort::SortOrder::Ascending, sort::NanPlacement::Last);
 }
 
 /// Calculate prime numbers up to n (sieve of Eratosthenes)
+///
+/// The result holds π(n) ≈ n / ln n primes, about 200 MB for n = 10⁹; use
+/// `PrimeSieve` to stream them or `prime_count` to only count them.
 #[wasm_bindgen]
-pub fn calculate_primes(n: u32) -> Vec<u32> {
-    if n < 2 {
-        return vec![];
-    }
-    
-    let mut is_prime = vec![true; (n + 1) as usize];
-    is_prime[0] = false;
-    is_prime[1] = false;
-    
-    for i in 2..=((n as f64).sqrt() as u32) {
-        if is_prime[i as usize] {
-            let mut j = i * i;
-            while j <= n {
-                is_prime[j as usize] = false;
-                j += i;
-            }
-        }
-    }
-    
-    is_prime
-        .iter()
-        .enumerate()
-        .filter_map(|(i, &prime)| if prime { Some(i as u32) } else { None })
-        .collect()
+pub fn calculate_primes(n: u32) -> Result<Vec<u32>, JsError> {
+    Ok(sieve(n)?)
+}
+
+fn sieve(n: u32) -> error::Result<Vec<u32>> {
+    // Sieving takes constant 32 KiB segments; the cost is the 4 π(n) byte
+    // result
+    primes::primes_between(0, u64::from(n) + 1)
 }
 
 #[cfg(test)]
@@ -197,19 +177,42 @@ mod tests {
 
     #[test]
     fn test_add() {
-        assert_eq!(add(2, 3), 5);
+        assert_eq!(add(2, 3).unwrap(), 5);
     }
 
     #[test]
     fn test_fibonacci() {
-        assert_eq!(fibonacci(0), 0);
-        assert_eq!(fibonacci(1), 1);
-        assert_eq!(fibonacci(10), 55);
+        assert_eq!(fibonacci(0).unwrap(), 0);
+        assert_eq!(fibonacci(1).unwrap(), 1);
+        assert_eq!(fibonacci(10).unwrap(), 55);
+        assert_eq!(fibonacci(93).unwrap(), 12_200_160_415_121_876_738);
     }
 
     #[test]
     fn test_sum_array() {
         let data = vec![1.0, 2.0, 3.0, 4.0, 5.0];
-        assert_eq!(sum_array(&data), 15.0);
+        assert_eq!(sum_array(&data, None).unwrap(), 15.0);
+        assert_eq!(sum_array(&data, Some(SumMethod::Exact)).unwrap(), 15.0);
+    }
+
+    #[test]
+    fn test_matrix_multiply_dimensions() {
+        let a = vec![1.0, 2.0, 3.0, 4.0];
+        let b = vec![5.0, 6.0, 7.0, 8.0];
+        assert_eq!(matrix_multiply(a.clone(), b, 2).unwrap(), vec![19.0, 22.0, 43.0, 50.0]);
+        assert_eq!(
+            gemm::multiply(&a, &[1.0, 2.0], 2, 2, 2),
+            Err(ComputeError::DimensionMismatch { what: "b", expected: 4, actual: 2 })
+        );
+        assert!(matches!(
+            gemm::multiply(&a, &a, usize::MAX, usize::MAX, usize::MAX),
+            Err(ComputeError::Overflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_calculate_primes() {
+        assert_eq!(calculate_primes(20).unwrap(), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+        assert!(sieve(1).unwrap().is_empty());
     }
 }
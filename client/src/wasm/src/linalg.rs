@@ -0,0 +1,437 @@
+//! Dense linear algebra on `Matrix`: LU with partial pivoting, Householder
+//! QR, Cholesky, and the solve/inverse/determinant helpers built on them.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, ComputeError, Result};
+use crate::matrix::Matrix;
+
+pub(crate) fn require_square(a: &Matrix) -> Result<usize> {
+    check_len("matrix columns", a.cols(), a.rows())?;
+    Ok(a.rows())
+}
+
+/// Largest absolute entry, used to scale singularity tolerances
+fn max_abs(a: &Matrix) -> f64 {
+    a.data().iter().fold(0.0, |m, x| m.max(x.abs()))
+}
+
+/// `PA = LU`, with `L` unit lower triangular and both factors packed in `lu`.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct LuDecomposition {
+    lu: Matrix,
+    /// Row `i` of `PA` is row `perm[i]` of `A`
+    perm: Vec<usize>,
+    /// Determinant of `P`
+    sign: f64,
+    singular: bool,
+}
+
+impl LuDecomposition {
+    /// Factor without failing on singular input; `determinant` needs that.
+    fn factor(a: &Matrix) -> Result<LuDecomposition> {
+        let n = require_square(a)?;
+        let tol = f64::EPSILON * n as f64 * max_abs(a);
+        let mut lu = a.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+        let mut singular = n > 0 && tol == 0.0;
+        let d = lu.data_mut();
+
+        for k in 0..n {
+            let p = (k..n)
+                .max_by(|&i, &j| d[i * n + k].abs().total_cmp(&d[j * n + k].abs()))
+                .unwrap_or(k);
+            if d[p * n + k].abs() <= tol {
+                singular = true;
+            }
+            if d[p * n + k] == 0.0 {
+                // Column already eliminated; nothing to pivot on
+                continue;
+            }
+            if p != k {
+                for j in 0..n {
+                    d.swap(k * n + j, p * n + j);
+                }
+                perm.swap(k, p);
+                sign = -sign;
+            }
+            let pivot = d[k * n + k];
+            for i in k + 1..n {
+                let f = d[i * n + k] / pivot;
+                d[i * n + k] = f;
+                for j in k + 1..n {
+                    d[i * n + j] -= f * d[k * n + j];
+                }
+            }
+        }
+
+        Ok(LuDecomposition {
+            lu,
+            perm,
+            sign,
+            singular,
+        })
+    }
+
+    pub fn compute(a: &Matrix) -> Result<LuDecomposition> {
+        let lu = LuDecomposition::factor(a)?;
+        if lu.singular {
+            return Err(ComputeError::SingularMatrix);
+        }
+        Ok(lu)
+    }
+
+    pub fn det(&self) -> f64 {
+        let n = self.lu.rows();
+        (0..n).fold(self.sign, |acc, i| acc * self.lu.at(i, i))
+    }
+
+    /// Solve `A X = B` for an `n×k` right-hand side
+    pub fn solve_matrix(&self, b: &Matrix) -> Result<Matrix> {
+        if self.singular {
+            return Err(ComputeError::SingularMatrix);
+        }
+        let n = self.lu.rows();
+        check_len("right-hand side rows", b.rows(), n)?;
+        let k = b.cols();
+        let lu = self.lu.data();
+        let mut x = Matrix::zeros(n, k)?;
+        let xd = x.data_mut();
+
+        for (i, &src) in self.perm.iter().enumerate() {
+            xd[i * k..(i + 1) * k].copy_from_slice(b.row(src));
+        }
+        for c in 0..k {
+            // Forward substitution with unit-diagonal L
+            for i in 0..n {
+                let s: f64 = (0..i).map(|j| lu[i * n + j] * xd[j * k + c]).sum();
+                xd[i * k + c] -= s;
+            }
+            // Back substitution with U
+            for i in (0..n).rev() {
+                let s: f64 = (i + 1..n).map(|j| lu[i * n + j] * xd[j * k + c]).sum();
+                xd[i * k + c] = (xd[i * k + c] - s) / lu[i * n + i];
+            }
+        }
+        Ok(x)
+    }
+
+    pub fn lower(&self) -> Result<Matrix> {
+        let n = self.lu.rows();
+        let mut l = Matrix::identity(n)?;
+        for i in 0..n {
+            for j in 0..i {
+                l.data_mut()[i * n + j] = self.lu.at(i, j);
+            }
+        }
+        Ok(l)
+    }
+
+    pub fn upper(&self) -> Result<Matrix> {
+        let n = self.lu.rows();
+        let mut u = Matrix::zeros(n, n)?;
+        for i in 0..n {
+            for j in i..n {
+                u.data_mut()[i * n + j] = self.lu.at(i, j);
+            }
+        }
+        Ok(u)
+    }
+}
+
+#[wasm_bindgen]
+impl LuDecomposition {
+    /// Unit lower triangular factor `L`
+    #[wasm_bindgen(getter)]
+    pub fn l(&self) -> Result<Matrix, JsError> {
+        Ok(self.lower()?)
+    }
+
+    /// Upper triangular factor `U`
+    #[wasm_bindgen(getter)]
+    pub fn u(&self) -> Result<Matrix, JsError> {
+        Ok(self.upper()?)
+    }
+
+    /// Row permutation: row `i` of `PA` is row `pivots[i]` of `A`
+    #[wasm_bindgen(getter)]
+    pub fn pivots(&self) -> Vec<u32> {
+        self.perm.iter().map(|&p| p as u32).collect()
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.det()
+    }
+
+    /// Solve `A X = B` reusing this factorization
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, JsError> {
+        Ok(self.solve_matrix(b)?)
+    }
+}
+
+/// Thin QR factorization `A = QR` of an `m×n` matrix with `m >= n`.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct QrDecomposition {
+    q: Matrix,
+    r: Matrix,
+}
+
+impl QrDecomposition {
+    /// Householder reflections applied column by column
+    pub fn compute(a: &Matrix) -> Result<QrDecomposition> {
+        let (m, n) = (a.rows(), a.cols());
+        if m < n {
+            return Err(ComputeError::invalid(format!(
+                "QR needs at least as many rows as columns, got {m}x{n}"
+            )));
+        }
+        let mut r = a.clone();
+        let mut reflectors: Vec<Vec<f64>> = Vec::with_capacity(n);
+
+        for k in 0..n {
+            let rd = r.data_mut();
+            let mut v: Vec<f64> = (k..m).map(|i| rd[i * n + k]).collect();
+            let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                reflectors.push(vec![0.0; m - k]);
+                continue;
+            }
+            let alpha = if v[0] >= 0.0 { -norm } else { norm };
+            v[0] -= alpha;
+            let vnorm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            v.iter_mut().for_each(|x| *x /= vnorm);
+
+            for j in k..n {
+                let dot: f64 = v
+                    .iter()
+                    .enumerate()
+                    .map(|(i, vi)| vi * rd[(k + i) * n + j])
+                    .sum();
+                for (i, vi) in v.iter().enumerate() {
+                    rd[(k + i) * n + j] -= 2.0 * vi * dot;
+                }
+            }
+            reflectors.push(v);
+        }
+
+        // Q = H_0 H_1 ... H_{n-1} applied to the first n columns of I
+        let mut q = Matrix::zeros(m, n)?;
+        let qd = q.data_mut();
+        for j in 0..n {
+            qd[j * n + j] = 1.0;
+        }
+        for (k, v) in reflectors.iter().enumerate().rev() {
+            for j in 0..n {
+                let dot: f64 = v
+                    .iter()
+                    .enumerate()
+                    .map(|(i, vi)| vi * qd[(k + i) * n + j])
+                    .sum();
+                for (i, vi) in v.iter().enumerate() {
+                    qd[(k + i) * n + j] -= 2.0 * vi * dot;
+                }
+            }
+        }
+
+        let mut upper = Matrix::zeros(n, n)?;
+        for i in 0..n {
+            for j in i..n {
+                upper.data_mut()[i * n + j] = r.at(i, j);
+            }
+        }
+
+        Ok(QrDecomposition { q, r: upper })
+    }
+
+    pub fn q_factor(&self) -> &Matrix {
+        &self.q
+    }
+
+    pub fn r_factor(&self) -> &Matrix {
+        &self.r
+    }
+}
+
+#[wasm_bindgen]
+impl QrDecomposition {
+    /// `m×n` factor with orthonormal columns
+    #[wasm_bindgen(getter)]
+    pub fn q(&self) -> Matrix {
+        self.q.clone()
+    }
+
+    /// `n×n` upper triangular factor
+    #[wasm_bindgen(getter)]
+    pub fn r(&self) -> Matrix {
+        self.r.clone()
+    }
+}
+
+/// Square and symmetric up to rounding; `what` names the caller in the error
+pub(crate) fn require_symmetric(a: &Matrix, what: &str) -> Result<usize> {
+    let n = require_square(a)?;
+    let tol = f64::EPSILON * n as f64 * max_abs(a);
+    for i in 0..n {
+        for j in 0..i {
+            if (a.at(i, j) - a.at(j, i)).abs() > tol {
+                return Err(ComputeError::invalid(format!(
+                    "{what} needs a symmetric matrix"
+                )));
+            }
+        }
+    }
+    Ok(n)
+}
+
+/// Lower triangular `L` with `A = L Lᵀ` for symmetric positive definite `A`
+pub fn cholesky(a: &Matrix) -> Result<Matrix> {
+    let n = require_symmetric(a, "Cholesky")?;
+
+    let mut l = Matrix::zeros(n, n)?;
+    let ld = l.data_mut();
+    for j in 0..n {
+        let d = a.at(j, j) - (0..j).map(|k| ld[j * n + k] * ld[j * n + k]).sum::<f64>();
+        if d <= 0.0 || !d.is_finite() {
+            return Err(ComputeError::NotPositiveDefinite);
+        }
+        let d = d.sqrt();
+        ld[j * n + j] = d;
+        for i in j + 1..n {
+            let s: f64 = (0..j).map(|k| ld[i * n + k] * ld[j * n + k]).sum();
+            ld[i * n + j] = (a.at(i, j) - s) / d;
+        }
+    }
+    Ok(l)
+}
+
+/// Determinant via LU; exactly singular input gives 0 rather than an error
+pub fn det(a: &Matrix) -> Result<f64> {
+    Ok(LuDecomposition::factor(a)?.det())
+}
+
+pub fn inv(a: &Matrix) -> Result<Matrix> {
+    let n = require_square(a)?;
+    LuDecomposition::compute(a)?.solve_matrix(&Matrix::identity(n)?)
+}
+
+/// LU factorization with partial pivoting; throws if `a` is singular
+#[wasm_bindgen(js_name = lu)]
+pub fn js_lu(a: &Matrix) -> Result<LuDecomposition, JsError> {
+    Ok(LuDecomposition::compute(a)?)
+}
+
+/// Householder QR factorization of an `m×n` matrix (`m >= n`)
+#[wasm_bindgen(js_name = qr)]
+pub fn js_qr(a: &Matrix) -> Result<QrDecomposition, JsError> {
+    Ok(QrDecomposition::compute(a)?)
+}
+
+/// Cholesky factor `L` of a symmetric positive definite matrix
+#[wasm_bindgen(js_name = cholesky)]
+pub fn js_cholesky(a: &Matrix) -> Result<Matrix, JsError> {
+    Ok(cholesky(a)?)
+}
+
+/// Solve `A X = B`; `b` is `n×k` (use `k = 1` for a single vector)
+#[wasm_bindgen]
+pub fn solve(a: &Matrix, b: &Matrix) -> Result<Matrix, JsError> {
+    Ok(LuDecomposition::compute(a)?.solve_matrix(b)?)
+}
+
+#[wasm_bindgen]
+pub fn inverse(a: &Matrix) -> Result<Matrix, JsError> {
+    Ok(inv(a)?)
+}
+
+#[wasm_bindgen]
+pub fn determinant(a: &Matrix) -> Result<f64, JsError> {
+    Ok(det(a)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mat(data: &[f64], rows: usize, cols: usize) -> Matrix {
+        Matrix::from_vec(data.to_vec(), rows, cols).unwrap()
+    }
+
+    fn assert_close(a: &Matrix, b: &Matrix) {
+        assert_eq!((a.rows(), a.cols()), (b.rows(), b.cols()));
+        for (x, y) in a.data().iter().zip(b.data()) {
+            assert!((x - y).abs() < 1e-10, "{:?} != {:?}", a.data(), b.data());
+        }
+    }
+
+    #[test]
+    fn test_lu_solve_inverse_determinant() {
+        // Leading zero forces a row swap
+        let a = mat(&[0.0, 2.0, 1.0, 1.0, 1.0, 0.0, 3.0, 0.0, 1.0], 3, 3);
+        let lu = LuDecomposition::compute(&a).unwrap();
+        let pa = lu.lower().unwrap().matmul(&lu.upper().unwrap()).unwrap();
+        for (i, &p) in lu.perm.iter().enumerate() {
+            assert_eq!(pa.row(i), a.row(p));
+        }
+        assert!((det(&a).unwrap() + 5.0).abs() < 1e-12);
+
+        let x = LuDecomposition::compute(&a)
+            .unwrap()
+            .solve_matrix(&mat(&[3.0, 2.0, 4.0], 3, 1))
+            .unwrap();
+        assert_close(&a.matmul(&x).unwrap(), &mat(&[3.0, 2.0, 4.0], 3, 1));
+        assert_close(
+            &a.matmul(&inv(&a).unwrap()).unwrap(),
+            &Matrix::identity(3).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_singular() {
+        let a = mat(&[1.0, 2.0, 2.0, 4.0], 2, 2);
+        assert_eq!(det(&a).unwrap(), 0.0);
+        assert_eq!(inv(&a).unwrap_err(), ComputeError::SingularMatrix);
+        assert!(det(&mat(&[1.0; 6], 2, 3)).is_err());
+    }
+
+    #[test]
+    fn test_qr() {
+        let a = mat(
+            &[
+                12.0, -51.0, 4.0, 6.0, 167.0, -68.0, -4.0, 24.0, -41.0, 1.0, 1.0, 1.0,
+            ],
+            4,
+            3,
+        );
+        let qr = QrDecomposition::compute(&a).unwrap();
+        assert_close(&qr.q_factor().matmul(qr.r_factor()).unwrap(), &a);
+        let qtq = qr
+            .q_factor()
+            .transposed()
+            .unwrap()
+            .matmul(qr.q_factor())
+            .unwrap();
+        assert_close(&qtq, &Matrix::identity(3).unwrap());
+        assert!(QrDecomposition::compute(&a.transposed().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_cholesky() {
+        let a = mat(
+            &[4.0, 12.0, -16.0, 12.0, 37.0, -43.0, -16.0, -43.0, 98.0],
+            3,
+            3,
+        );
+        let l = cholesky(&a).unwrap();
+        assert_close(
+            &l,
+            &mat(&[2.0, 0.0, 0.0, 6.0, 1.0, 0.0, -8.0, 5.0, 3.0], 3, 3),
+        );
+        assert_eq!(
+            cholesky(&mat(&[1.0, 2.0, 2.0, 1.0], 2, 2)).unwrap_err(),
+            ComputeError::NotPositiveDefinite
+        );
+    }
+}
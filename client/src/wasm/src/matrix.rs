@@ -0,0 +1,236 @@
+//! Dense row-major matrix that stays in WASM memory between calls.
+//!
+//! `matrix_multiply` copies its inputs into WASM and the result back out on
+//! every call. A `Matrix` is created once, operated on through its methods
+//! and only read back when needed, either as a copy (`toArray`) or as a
+//! zero-copy view (`view`).
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, checked_area, try_filled, ComputeError, Result};
+use crate::gemm;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    /// Wrap a row-major buffer of `rows * cols` elements
+    pub fn from_vec(data: Vec<f64>, rows: usize, cols: usize) -> Result<Matrix> {
+        check_len("matrix data", data.len(), checked_area(rows, cols)?)?;
+        Ok(Matrix { rows, cols, data })
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Result<Matrix> {
+        let data = try_filled(checked_area(rows, cols)?, 0.0)?;
+        Ok(Matrix { rows, cols, data })
+    }
+
+    pub fn identity(n: usize) -> Result<Matrix> {
+        let mut m = Matrix::zeros(n, n)?;
+        for i in 0..n {
+            m.data[i * n + i] = 1.0;
+        }
+        Ok(m)
+    }
+
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [f64] {
+        &mut self.data
+    }
+
+    pub fn into_vec(self) -> Vec<f64> {
+        self.data
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Row `r` as a slice
+    pub fn row(&self, r: usize) -> &[f64] {
+        &self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn at(&self, r: usize, c: usize) -> f64 {
+        self.data[r * self.cols + c]
+    }
+
+    fn index(&self, r: usize, c: usize) -> Result<usize> {
+        if r >= self.rows || c >= self.cols {
+            return Err(ComputeError::invalid(format!(
+                "index ({r}, {c}) out of bounds for {}x{} matrix",
+                self.rows, self.cols
+            )));
+        }
+        Ok(r * self.cols + c)
+    }
+
+    pub fn matmul(&self, other: &Matrix) -> Result<Matrix> {
+        check_len("right-hand matrix rows", other.rows, self.cols)?;
+        let data = gemm::multiply(&self.data, &other.data, self.rows, self.cols, other.cols)?;
+        Ok(Matrix {
+            rows: self.rows,
+            cols: other.cols,
+            data,
+        })
+    }
+
+    /// Element-wise sum
+    pub fn sum(&self, other: &Matrix) -> Result<Matrix> {
+        check_len("right-hand matrix rows", other.rows, self.rows)?;
+        check_len("right-hand matrix cols", other.cols, self.cols)?;
+        let mut out = self.clone();
+        for (x, &y) in out.data.iter_mut().zip(&other.data) {
+            *x += y;
+        }
+        Ok(out)
+    }
+
+    pub fn transposed(&self) -> Result<Matrix> {
+        let mut out = Matrix::zeros(self.cols, self.rows)?;
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                out.data[c * self.rows + r] = self.data[r * self.cols + c];
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn scaled(&self, factor: f64) -> Matrix {
+        let mut out = self.clone();
+        out.data.iter_mut().for_each(|x| *x *= factor);
+        out
+    }
+}
+
+#[wasm_bindgen]
+impl Matrix {
+    /// Zero-filled `rows × cols` matrix
+    #[wasm_bindgen(constructor)]
+    pub fn new(rows: usize, cols: usize) -> Result<Matrix, JsError> {
+        Ok(Matrix::zeros(rows, cols)?)
+    }
+
+    /// Copy a row-major `Float64Array` into a new matrix
+    #[wasm_bindgen(js_name = fromArray)]
+    pub fn js_from_array(data: Vec<f64>, rows: usize, cols: usize) -> Result<Matrix, JsError> {
+        Ok(Matrix::from_vec(data, rows, cols)?)
+    }
+
+    #[wasm_bindgen(js_name = identity)]
+    pub fn js_identity(n: usize) -> Result<Matrix, JsError> {
+        Ok(Matrix::identity(n)?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Zero-copy view of the matrix data.
+    ///
+    /// The view aliases WASM linear memory: it is invalidated when the memory
+    /// grows (any allocation may cause that) or the matrix is freed. Read it
+    /// immediately, or use `toArray` for a copy that can be kept.
+    pub fn view(&self) -> js_sys::Float64Array {
+        // SAFETY: the caller-facing contract above; no Rust allocation
+        // happens between creating the view and returning it.
+        unsafe { js_sys::Float64Array::view(&self.data) }
+    }
+
+    /// Copy of the matrix data
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Vec<f64> {
+        self.data.clone()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Result<f64, JsError> {
+        Ok(self.data[self.index(row, col)?])
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: f64) -> Result<(), JsError> {
+        let i = self.index(row, col)?;
+        self.data[i] = value;
+        Ok(())
+    }
+
+    /// Matrix product `self · other` (blocked GEMM kernel)
+    ///
+    /// Not called `multiply`: the export would be named `matrix_multiply`,
+    /// which is already taken by the free function.
+    #[wasm_bindgen(js_name = matmul)]
+    pub fn js_matmul(&self, other: &Matrix) -> Result<Matrix, JsError> {
+        Ok(self.matmul(other)?)
+    }
+
+    /// Element-wise sum `self + other`
+    pub fn add(&self, other: &Matrix) -> Result<Matrix, JsError> {
+        Ok(self.sum(other)?)
+    }
+
+    pub fn transpose(&self) -> Result<Matrix, JsError> {
+        Ok(self.transposed()?)
+    }
+
+    /// Multiply every element by `factor`
+    pub fn scale(&self, factor: f64) -> Matrix {
+        self.scaled(factor)
+    }
+
+    /// Multiply every element by `factor` without allocating a new matrix
+    #[wasm_bindgen(js_name = scaleInPlace)]
+    pub fn scale_in_place(&mut self, factor: f64) {
+        self.data.iter_mut().for_each(|x| *x *= factor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chained_operations() {
+        let a = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2, 3).unwrap();
+        let at = a.transpose().unwrap();
+        assert_eq!((at.rows(), at.cols()), (3, 2));
+        assert_eq!(at.data(), &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+
+        // (A · Aᵀ + I) * 2
+        let out = a
+            .matmul(&at)
+            .unwrap()
+            .add(&Matrix::identity(2).unwrap())
+            .unwrap()
+            .scale(2.0);
+        assert_eq!(out.to_array(), vec![30.0, 64.0, 64.0, 156.0]);
+    }
+
+    #[test]
+    fn test_shape_errors() {
+        let a = Matrix::zeros(2, 3).unwrap();
+        assert_eq!(
+            a.matmul(&a),
+            Err(ComputeError::DimensionMismatch {
+                what: "right-hand matrix rows",
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert!(a.sum(&a.transposed().unwrap()).is_err());
+        assert!(Matrix::from_vec(vec![1.0; 5], 2, 3).is_err());
+        assert!(a.index(2, 0).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+//! Integer number theory on 64-bit values.
+//!
+//! Exports take and return `u64`/`i64`, which wasm-bindgen maps to JS
+//! `BigInt` (and `Vec<u64>` to `BigUint64Array`), so values above 2⁵³ are
+//! exact. Factorization strips small primes by trial division and splits
+//! what remains with Brent's variant of Pollard's rho, using the
+//! deterministic Miller-Rabin test from [`crate::primes`].
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ComputeError, Result};
+use crate::primes::{is_prime_u64, mul_mod, pow_mod};
+
+/// Trial division covers factors below this bound
+const TRIAL_LIMIT: u64 = 1 << 10;
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple; `Overflow` if it exceeds `u64`
+pub fn lcm(a: u64, b: u64) -> Result<u64> {
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+    (a / gcd(a, b))
+        .checked_mul(b)
+        .ok_or(ComputeError::Overflow("lcm exceeds u64"))
+}
+
+/// `(g, x, y)` with `a x + b y = g = gcd(a, b)` and `g ≥ 0`
+pub fn extended_gcd(a: i64, b: i64) -> Result<(i64, i64, i64)> {
+    let (g, x, y) = extended_gcd_i128(a.into(), b.into());
+    // Bézout coefficients are bounded by |b|/g and |a|/g, so only g itself
+    // (gcd(i64::MIN, 0) = 2⁶³) can fail to fit
+    match (i64::try_from(g), i64::try_from(x), i64::try_from(y)) {
+        (Ok(g), Ok(x), Ok(y)) => Ok((g, x, y)),
+        _ => Err(ComputeError::Overflow("gcd exceeds i64")),
+    }
+}
+
+fn extended_gcd_i128(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut s0, mut s1) = (1, 0);
+    let (mut t0, mut t1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    if r0 < 0 {
+        (-r0, -s0, -t0)
+    } else {
+        (r0, s0, t0)
+    }
+}
+
+fn check_modulus(m: u64) -> Result<()> {
+    if m == 0 {
+        Err(ComputeError::invalid("modulus must be positive"))
+    } else {
+        Ok(())
+    }
+}
+
+/// `base^exp mod m`
+pub fn mod_pow_u64(base: u64, exp: u64, m: u64) -> Result<u64> {
+    check_modulus(m)?;
+    Ok(pow_mod(base, exp, m))
+}
+
+/// `x` in `0..m` with `a x ≡ 1 (mod m)`; an error if `gcd(a, m) ≠ 1`
+pub fn mod_inverse_u64(a: u64, m: u64) -> Result<u64> {
+    check_modulus(m)?;
+    let (g, x, _) = extended_gcd_i128(i128::from(a % m), i128::from(m));
+    if g != 1 {
+        return Err(ComputeError::invalid(format!(
+            "{a} has no inverse modulo {m} (gcd {g})"
+        )));
+    }
+    Ok(x.rem_euclid(i128::from(m)) as u64)
+}
+
+/// A non-trivial factor of the odd composite `n` (Brent's cycle detection,
+/// with gcds batched over runs of 128 steps)
+fn pollard_rho(n: u64) -> u64 {
+    const BATCH: u64 = 128;
+    for c in 1u64.. {
+        let f = |x: u64| ((mul_mod(x, x, n) as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y, mut ys) = (2, 2, 2);
+        let (mut q, mut g, mut r) = (1, 1, 1);
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(y);
+                    q = mul_mod(q, x.abs_diff(y), n);
+                }
+                g = gcd(q, n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == n {
+            // The batch overshot: step through it one gcd at a time
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+    }
+    unreachable!("some polynomial x² + c splits every odd composite")
+}
+
+fn split(n: u64, factors: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime_u64(n) {
+        factors.push(n);
+        return;
+    }
+    let d = pollard_rho(n);
+    split(d, factors);
+    split(n / d, factors);
+}
+
+/// Prime factors of `n` in ascending order, repeated by multiplicity
+/// (`[]` for 1); `n` must be positive
+pub fn factorize_u64(mut n: u64) -> Result<Vec<u64>> {
+    if n == 0 {
+        return Err(ComputeError::invalid("cannot factorize 0"));
+    }
+    let mut factors = Vec::new();
+    for p in std::iter::once(2).chain((3..TRIAL_LIMIT).step_by(2)) {
+        if p * p > n {
+            break;
+        }
+        while n.is_multiple_of(p) {
+            factors.push(p);
+            n /= p;
+        }
+    }
+    split(n, &mut factors);
+    factors.sort_unstable();
+    Ok(factors)
+}
+
+/// Euler's totient φ(n): how many of `1..=n` are coprime to `n`
+pub fn totient_u64(n: u64) -> Result<u64> {
+    let mut factors = factorize_u64(n)?;
+    factors.dedup();
+    Ok(factors.iter().fold(n, |phi, &p| phi / p * (p - 1)))
+}
+
+#[wasm_bindgen(js_name = gcd)]
+pub fn js_gcd(a: u64, b: u64) -> u64 {
+    gcd(a, b)
+}
+
+#[wasm_bindgen(js_name = lcm)]
+pub fn js_lcm(a: u64, b: u64) -> Result<u64, JsError> {
+    Ok(lcm(a, b)?)
+}
+
+/// `[g, x, y]` (a `BigInt64Array`) with `a x + b y = g = gcd(a, b)`
+#[wasm_bindgen(js_name = extended_gcd)]
+pub fn js_extended_gcd(a: i64, b: i64) -> Result<Vec<i64>, JsError> {
+    let (g, x, y) = extended_gcd(a, b)?;
+    Ok(vec![g, x, y])
+}
+
+#[wasm_bindgen]
+pub fn mod_pow(base: u64, exp: u64, modulus: u64) -> Result<u64, JsError> {
+    Ok(mod_pow_u64(base, exp, modulus)?)
+}
+
+/// Throws when `a` and `modulus` are not coprime
+#[wasm_bindgen]
+pub fn mod_inverse(a: u64, modulus: u64) -> Result<u64, JsError> {
+    Ok(mod_inverse_u64(a, modulus)?)
+}
+
+#[wasm_bindgen]
+pub fn totient(n: u64) -> Result<u64, JsError> {
+    Ok(totient_u64(n)?)
+}
+
+/// Prime factors with multiplicity, ascending, as a `BigUint64Array`
+#[wasm_bindgen]
+pub fn factorize(n: u64) -> Result<Vec<u64>, JsError> {
+    Ok(factorize_u64(n)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factorize() {
+        for n in 1..3000u64 {
+            let f = factorize_u64(n).unwrap();
+            assert_eq!(f.iter().product::<u64>(), n);
+            assert!(
+                f.iter().all(|&p| is_prime_u64(p)) && f.is_sorted(),
+                "{n}: {f:?}"
+            );
+        }
+        let cases: [(u64, &[u64]); 5] = [
+            (u64::MAX, &[3, 5, 17, 257, 641, 65_537, 6_700_417]),
+            (18_446_744_073_709_551_557, &[18_446_744_073_709_551_557]),
+            // Two 32-bit primes: out of reach of trial division
+            (
+                4_294_967_291 * 4_294_967_279,
+                &[4_294_967_279, 4_294_967_291],
+            ),
+            (1 << 63, &[2; 63]),
+            (999_999_000_001 * 1_000_003, &[1_000_003, 999_999_000_001]),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(factorize_u64(n).unwrap(), expected);
+        }
+        assert!(factorize_u64(0).is_err());
+    }
+
+    #[test]
+    fn test_gcd_family() {
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd(84, 36), 12);
+        assert_eq!(lcm(4, 6).unwrap(), 12);
+        assert_eq!(lcm(0, 6).unwrap(), 0);
+        assert!(lcm(u64::MAX, u64::MAX - 1).is_err());
+
+        for (a, b) in [(240, 46), (-240, 46), (7, 0), (0, -5), (i64::MIN, 3)] {
+            let (g, x, y) = extended_gcd(a, b).unwrap();
+            assert!(g >= 0);
+            assert_eq!(a as i128 * x as i128 + b as i128 * y as i128, g as i128);
+        }
+        assert_eq!(extended_gcd(240, 46).unwrap().0, 2);
+        assert!(extended_gcd(i64::MIN, 0).is_err());
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_pow_u64(4, 13, 497).unwrap(), 445);
+        assert_eq!(mod_pow_u64(5, 0, 1).unwrap(), 0);
+        assert_eq!(
+            mod_pow_u64(u64::MAX, u64::MAX, 18_446_744_073_709_551_557).unwrap(),
+            mod_pow_u64(
+                58,
+                u64::MAX % 18_446_744_073_709_551_556,
+                18_446_744_073_709_551_557
+            )
+            .unwrap()
+        );
+        assert!(mod_pow_u64(2, 3, 0).is_err());
+
+        assert_eq!(mod_inverse_u64(3, 11).unwrap(), 4);
+        let m = 18_446_744_073_709_551_557;
+        let inv = mod_inverse_u64(u64::MAX, m).unwrap();
+        assert_eq!(mul_mod(u64::MAX, inv, m), 1);
+        assert!(mod_inverse_u64(6, 9).is_err());
+
+        assert_eq!(totient_u64(1).unwrap(), 1);
+        assert_eq!(totient_u64(36).unwrap(), 12);
+        assert_eq!(totient_u64(97).unwrap(), 96);
+        let brute = |n: u64| (1..=n).filter(|&k| gcd(k, n) == 1).count() as u64;
+        assert!((1..500).all(|n| totient_u64(n).unwrap() == brute(n)));
+    }
+}
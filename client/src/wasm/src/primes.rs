@@ -0,0 +1,344 @@
+//! Prime sieving and primality testing.
+//!
+//! The sieve is segmented and odd-only: each segment is a bitset of odd
+//! numbers (one bit per odd number, 32 KiB per segment) sieved by the odd
+//! primes up to `√hi`. Memory stays constant however large the range, and
+//! counting uses popcounts without ever listing the primes. Sieve ranges are
+//! limited to `u32` values; `is_prime` covers all of `u64`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ComputeError, Result};
+
+/// Odd numbers per segment (32 KiB of bits)
+const SEGMENT_BITS: u64 = 32 * 1024 * 8;
+/// Exclusive upper bound of the sieve domain
+const SIEVE_LIMIT: u64 = 1 << 32;
+
+pub(crate) fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+pub(crate) fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut acc = 1 % m;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul_mod(acc, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    acc
+}
+
+/// Integer square root (floor)
+pub(crate) fn isqrt(n: u64) -> u64 {
+    let mut r = (n as f64).sqrt() as u64;
+    while r * r > n {
+        r -= 1;
+    }
+    while (r + 1).checked_mul(r + 1).is_some_and(|s| s <= n) {
+        r += 1;
+    }
+    r
+}
+
+/// Deterministic Miller-Rabin: the first twelve primes as bases are exact
+/// for every `n < 3.18 × 10²³`, so for all of `u64`
+pub fn is_prime_u64(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    for p in BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Odd primes up to and including `limit`, by a plain odd-only sieve
+fn odd_primes_to(limit: u64) -> Vec<u64> {
+    // Bit i stands for 2i + 1
+    let bits = (limit as usize).div_ceil(2);
+    let mut composite = vec![false; bits];
+    let mut primes = Vec::new();
+    for i in 1..bits {
+        if composite[i] {
+            continue;
+        }
+        let p = 2 * i + 1;
+        primes.push(p as u64);
+        let mut j = p * p / 2;
+        while j < bits {
+            composite[j] = true;
+            j += p;
+        }
+    }
+    primes
+}
+
+/// Streams the primes in `[lo, hi)` one segment at a time
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct PrimeSieve {
+    base: Vec<u64>,
+    /// Odd number represented by bit 0 of the next segment
+    next: u64,
+    hi: u64,
+    /// Whether 2 is in range and not yet reported
+    two: bool,
+    /// Candidate bits of the current segment (1 = prime)
+    words: Vec<u64>,
+}
+
+impl PrimeSieve {
+    /// Sieve over `[lo, hi)`, with `hi` capped at 2³²
+    pub fn new(lo: u64, hi: u64) -> PrimeSieve {
+        let hi = hi.min(SIEVE_LIMIT);
+        let base = if hi > 9 {
+            odd_primes_to(isqrt(hi - 1))
+        } else {
+            Vec::new()
+        };
+        PrimeSieve {
+            base,
+            next: lo.max(1) | 1,
+            hi,
+            two: lo <= 2 && hi > 2,
+            words: Vec::new(),
+        }
+    }
+
+    /// Sieve the next segment into `words`; returns its first odd number and
+    /// bit count, or `None` when the range is exhausted
+    fn sieve_segment(&mut self) -> Option<(u64, u64)> {
+        let start = self.next;
+        if start >= self.hi {
+            return None;
+        }
+        let bits = ((self.hi - start).div_ceil(2)).min(SEGMENT_BITS);
+        self.next = start + 2 * bits;
+        let end = start + 2 * bits;
+
+        self.words.clear();
+        self.words.resize(bits.div_ceil(64) as usize, !0);
+        if !bits.is_multiple_of(64) {
+            *self.words.last_mut().unwrap() = (1 << (bits % 64)) - 1;
+        }
+        if start == 1 {
+            self.words[0] &= !1;
+        }
+        for &p in &self.base {
+            if p * p >= end {
+                break;
+            }
+            // First odd multiple of p that is at least max(p², start)
+            let mut m = (p * p).max(start.div_ceil(p) * p);
+            if m.is_multiple_of(2) {
+                m += p;
+            }
+            let mut k = (m - start) / 2;
+            while k < bits {
+                self.words[(k / 64) as usize] &= !(1 << (k % 64));
+                k += p;
+            }
+        }
+        Some((start, bits))
+    }
+
+    /// Append the primes of the next segment to `out`; `false` once exhausted
+    pub fn next_segment(&mut self, out: &mut Vec<u32>) -> Result<bool> {
+        let two = std::mem::take(&mut self.two);
+        let Some((start, _)) = self.sieve_segment() else {
+            if two {
+                out.push(2);
+            }
+            return Ok(two);
+        };
+        let count = self
+            .words
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum::<usize>();
+        out.try_reserve(count + 1)
+            .map_err(|_| ComputeError::AllocationFailure {
+                bytes: (count + 1) * 4,
+            })?;
+        if two {
+            out.push(2);
+        }
+        for (i, &word) in self.words.iter().enumerate() {
+            let mut w = word;
+            while w != 0 {
+                let k = i as u64 * 64 + w.trailing_zeros() as u64;
+                out.push((start + 2 * k) as u32);
+                w &= w - 1;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Number of primes left in the range, consuming the sieve
+    pub fn count(mut self) -> u64 {
+        let mut n = u64::from(self.two);
+        while self.sieve_segment().is_some() {
+            n += self
+                .words
+                .iter()
+                .map(|w| u64::from(w.count_ones()))
+                .sum::<u64>();
+        }
+        n
+    }
+}
+
+#[wasm_bindgen]
+impl PrimeSieve {
+    #[wasm_bindgen(constructor)]
+    pub fn js_new(lo: u32, hi: u32) -> PrimeSieve {
+        PrimeSieve::new(u64::from(lo), u64::from(hi))
+    }
+
+    /// Primes of the next segment (ascending); empty once the range is done
+    #[wasm_bindgen(js_name = nextBatch)]
+    pub fn next_batch(&mut self) -> Result<Vec<u32>, JsError> {
+        let mut out = Vec::new();
+        // Segments past the last prime can be empty; keep going until one isn't
+        while out.is_empty() && self.next_segment(&mut out)? {}
+        Ok(out)
+    }
+}
+
+/// All primes in `[lo, hi)`
+pub fn primes_between(lo: u64, hi: u64) -> Result<Vec<u32>> {
+    let mut sieve = PrimeSieve::new(lo, hi);
+    let mut out = Vec::new();
+    while sieve.next_segment(&mut out)? {}
+    Ok(out)
+}
+
+/// π(n): the number of primes `≤ n`
+pub fn count_primes(n: u32) -> u32 {
+    PrimeSieve::new(0, u64::from(n) + 1).count() as u32
+}
+
+/// Primes in `[lo, hi)` as a `Uint32Array`
+#[wasm_bindgen]
+pub fn primes_in_range(lo: u32, hi: u32) -> Result<Vec<u32>, JsError> {
+    Ok(primes_between(u64::from(lo), u64::from(hi))?)
+}
+
+/// Number of primes `≤ n`, without materializing them
+#[wasm_bindgen]
+pub fn prime_count(n: u32) -> u32 {
+    count_primes(n)
+}
+
+/// Deterministic primality test for any 64-bit `BigInt`
+#[wasm_bindgen]
+pub fn is_prime(n: u64) -> bool {
+    is_prime_u64(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trial_division(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn test_ranges() {
+        let all: Vec<u32> = (0..3000u32).filter(|&n| trial_division(n.into())).collect();
+        assert_eq!(primes_between(0, 3000).unwrap(), all);
+        for (lo, hi) in [
+            (0, 3),
+            (2, 3),
+            (3, 4),
+            (1, 2),
+            (10, 10),
+            (20, 10),
+            (997, 1010),
+        ] {
+            let expected: Vec<u32> = all
+                .iter()
+                .copied()
+                .filter(|p| (lo..hi).contains(p))
+                .collect();
+            assert_eq!(
+                primes_between(lo.into(), hi.into()).unwrap(),
+                expected,
+                "[{lo}, {hi})"
+            );
+        }
+        // Spans several segments, starting mid-segment
+        let lo = 3 * SEGMENT_BITS - 101;
+        let got = primes_between(lo, lo + 4 * SEGMENT_BITS).unwrap();
+        assert!(got.iter().all(|&p| trial_division(p.into())));
+        assert_eq!(
+            got.len() as u64,
+            PrimeSieve::new(lo, lo + 4 * SEGMENT_BITS).count()
+        );
+
+        let top = primes_between(u64::from(u32::MAX) - 100, u64::MAX).unwrap();
+        assert_eq!(top.last(), Some(&4_294_967_291));
+    }
+
+    #[test]
+    fn test_counts_and_batches() {
+        assert_eq!(count_primes(1), 0);
+        assert_eq!(count_primes(2), 1);
+        assert_eq!(count_primes(100), 25);
+        assert_eq!(count_primes(10_000_000), 664_579);
+
+        let mut sieve = PrimeSieve::js_new(1_000_000, 3_000_000);
+        let (mut total, mut last) = (0, 0);
+        loop {
+            let batch = sieve.next_batch().unwrap();
+            if batch.is_empty() {
+                break;
+            }
+            assert!(batch[0] > last);
+            last = *batch.last().unwrap();
+            total += batch.len();
+        }
+        assert_eq!(
+            total as u32,
+            count_primes(2_999_999) - count_primes(999_999)
+        );
+    }
+
+    #[test]
+    fn test_miller_rabin() {
+        for n in 0..2000 {
+            assert_eq!(is_prime_u64(n), trial_division(n), "{n}");
+        }
+        assert!(is_prime_u64(18_446_744_073_709_551_557)); // largest u64 prime
+        assert!(!is_prime_u64(u64::MAX));
+        // Strong pseudoprimes to smaller base sets
+        assert!(!is_prime_u64(3_215_031_751));
+        assert!(!is_prime_u64(3_825_123_056_546_413_051));
+        assert!(!is_prime_u64(4_294_967_297)); // 641 × 6700417
+    }
+}
@@ -0,0 +1,333 @@
+//! RGBA resampling.
+//!
+//! Separable: rows are resampled to the new width, then columns to the new
+//! height. When shrinking, the filter is widened by the scale factor so every
+//! source pixel contributes (area-averaging rather than skipping pixels),
+//! which is what makes thumbnails free of aliasing. Interpolation happens in
+//! premultiplied alpha.
+
+use std::f32::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{checked_area, try_filled, ComputeError, Result};
+use crate::image::{check_image, premultiplied, store_premultiplied};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeFilter {
+    /// Nearest source pixel, no blending
+    Nearest = 0,
+    /// Triangle filter
+    Bilinear = 1,
+    /// Cubic convolution with a = -0.5 (Catmull-Rom)
+    Bicubic = 2,
+    /// Windowed sinc with three lobes
+    #[default]
+    Lanczos3 = 3,
+}
+
+impl ResizeFilter {
+    /// Radius of the kernel in source pixels, before widening for downscaling
+    pub(crate) fn support(self) -> f32 {
+        match self {
+            ResizeFilter::Nearest => 0.5,
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Bicubic => 2.0,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    pub(crate) fn weight(self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Nearest => f32::from(u8::from(x < 0.5)),
+            ResizeFilter::Bilinear => (1.0 - x).max(0.0),
+            ResizeFilter::Bicubic => {
+                const A: f32 = -0.5;
+                if x < 1.0 {
+                    ((A + 2.0) * x - (A + 3.0)) * x * x + 1.0
+                } else if x < 2.0 {
+                    ((A * x - 5.0 * A) * x + 8.0 * A) * x - 4.0 * A
+                } else {
+                    0.0
+                }
+            }
+            ResizeFilter::Lanczos3 => {
+                if x == 0.0 {
+                    1.0
+                } else if x < 3.0 {
+                    let px = PI * x;
+                    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Source window and normalized weights for every output coordinate
+fn contributions(src: usize, dst: usize, filter: ResizeFilter) -> Vec<(usize, Vec<f32>)> {
+    let scale = src as f32 / dst as f32;
+    let filter_scale = scale.max(1.0);
+    let support = filter.support() * filter_scale;
+    (0..dst)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale;
+            let lo = ((center - support).floor().max(0.0) as usize).min(src - 1);
+            let hi = ((center + support).ceil() as usize).clamp(lo + 1, src);
+            let mut w: Vec<f32> = (lo..hi)
+                .map(|j| filter.weight((j as f32 + 0.5 - center) / filter_scale))
+                .collect();
+            let total: f32 = w.iter().sum();
+            if total != 0.0 {
+                w.iter_mut().for_each(|x| *x /= total);
+            }
+            (lo, w)
+        })
+        .collect()
+}
+
+/// Resample `lines` runs of 4-channel pixels along one axis
+///
+/// Run `l` starts at `l * src_line` floats and its pixel `i` is `src_stride`
+/// floats further on each step; the output is laid out the same way with one
+/// pixel per entry of `weights`.
+fn resample_axis(
+    src: &[f32],
+    lines: usize,
+    (src_line, dst_line): (usize, usize),
+    (src_stride, dst_stride): (usize, usize),
+    weights: &[(usize, Vec<f32>)],
+    out: &mut [f32],
+) {
+    for line in 0..lines {
+        let (s0, d0) = (line * src_line, line * dst_line);
+        for (i, (lo, w)) in weights.iter().enumerate() {
+            let mut acc = [0.0f32; 4];
+            for (k, &wk) in w.iter().enumerate() {
+                let p = s0 + (lo + k) * src_stride;
+                for c in 0..4 {
+                    acc[c] += wk * src[p + c];
+                }
+            }
+            let q = d0 + i * dst_stride;
+            out[q..q + 4].copy_from_slice(&acc);
+        }
+    }
+}
+
+/// Number of floats in a `width × height` RGBA buffer
+fn rgba_floats(width: usize, height: usize) -> Result<usize> {
+    checked_area(width, height)?
+        .checked_mul(4)
+        .ok_or(ComputeError::Overflow("image dimensions"))
+}
+
+/// Resize a `src_width × src_height` RGBA buffer to `dst_width × dst_height`
+pub fn resize_rgba(
+    data: &[u8],
+    src_width: u32,
+    src_height: u32,
+    dst_width: u32,
+    dst_height: u32,
+    filter: ResizeFilter,
+) -> Result<Vec<u8>> {
+    let src_pixels = check_image(data, src_width, src_height)?;
+    let (sw, sh) = (src_width as usize, src_height as usize);
+    let (dw, dh) = (dst_width as usize, dst_height as usize);
+    let dst_bytes = rgba_floats(dw, dh)?;
+    if dst_bytes == 0 {
+        return Ok(Vec::new());
+    }
+    if src_pixels == 0 {
+        return Err(ComputeError::invalid("cannot resize an empty image"));
+    }
+
+    // Source index of destination index `i` on an axis; u64 so that the
+    // product cannot overflow a 32-bit usize
+    let nearest = |i: usize, src: usize, dst: usize| (i as u64 * src as u64 / dst as u64) as usize;
+
+    if filter == ResizeFilter::Nearest {
+        let mut out = Vec::new();
+        out.try_reserve_exact(dst_bytes)
+            .map_err(|_| ComputeError::AllocationFailure { bytes: dst_bytes })?;
+        let xs: Vec<usize> = (0..dw).map(|x| nearest(x, sw, dw)).collect();
+        for y in 0..dh {
+            let row = &data[nearest(y, sh, dh) * sw * 4..];
+            for &x in &xs {
+                out.extend_from_slice(&row[x * 4..x * 4 + 4]);
+            }
+        }
+        return Ok(out);
+    }
+
+    let src = premultiplied(data);
+    let mut resized = try_filled(dst_bytes, 0.0f32)?;
+    // The intermediate image is dw × sh when rows go first and sw × dh when
+    // columns do; take the order with the smaller one
+    if (sh as u64) * (dw as u64) <= (dh as u64) * (sw as u64) {
+        // Rows first: sh lines of sw pixels become sh lines of dw pixels
+        let mut wide = try_filled(rgba_floats(dw, sh)?, 0.0f32)?;
+        resample_axis(
+            &src,
+            sh,
+            (sw * 4, dw * 4),
+            (4, 4),
+            &contributions(sw, dw, filter),
+            &mut wide,
+        );
+        // Then columns: dw lines of sh pixels (stride one row) become dh pixels
+        resample_axis(
+            &wide,
+            dw,
+            (4, 4),
+            (dw * 4, dw * 4),
+            &contributions(sh, dh, filter),
+            &mut resized,
+        );
+    } else {
+        // Columns first: sw lines of sh pixels become sw lines of dh pixels
+        let mut tall = try_filled(rgba_floats(sw, dh)?, 0.0f32)?;
+        resample_axis(
+            &src,
+            sw,
+            (4, 4),
+            (sw * 4, sw * 4),
+            &contributions(sh, dh, filter),
+            &mut tall,
+        );
+        // Then rows: dh lines of sw pixels become dh lines of dw pixels
+        resample_axis(
+            &tall,
+            dh,
+            (sw * 4, dw * 4),
+            (4, 4),
+            &contributions(sw, dw, filter),
+            &mut resized,
+        );
+    }
+    let mut out = try_filled(dst_bytes, 0u8)?;
+    store_premultiplied(&resized, &mut out);
+    Ok(out)
+}
+
+/// Resize `ImageData` pixels; returns a new `dstWidth × dstHeight` RGBA buffer
+#[wasm_bindgen]
+pub fn resize(
+    data: &[u8],
+    src_width: u32,
+    src_height: u32,
+    dst_width: u32,
+    dst_height: u32,
+    filter: ResizeFilter,
+) -> Result<Vec<u8>, JsError> {
+    Ok(resize_rgba(
+        data, src_width, src_height, dst_width, dst_height, filter,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILTERS: [ResizeFilter; 4] = [
+        ResizeFilter::Nearest,
+        ResizeFilter::Bilinear,
+        ResizeFilter::Bicubic,
+        ResizeFilter::Lanczos3,
+    ];
+
+    #[test]
+    fn test_uniform_stays_uniform() {
+        let src: Vec<u8> = [12, 200, 99, 180].repeat(7 * 5);
+        for filter in FILTERS {
+            for (w, h) in [(3, 2), (14, 11), (7, 5), (1, 1)] {
+                let out = resize_rgba(&src, 7, 5, w, h, filter).unwrap();
+                assert_eq!(
+                    out,
+                    [12, 200, 99, 180].repeat((w * h) as usize),
+                    "{filter:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearest_and_downscale() {
+        let src = [1, 1, 1, 255, 2, 2, 2, 255];
+        let up = resize_rgba(&src, 2, 1, 4, 2, ResizeFilter::Nearest).unwrap();
+        assert_eq!(
+            &up[..16],
+            &[1, 1, 1, 255, 1, 1, 1, 255, 2, 2, 2, 255, 2, 2, 2, 255]
+        );
+        assert_eq!(&up[16..], &up[..16]);
+
+        // Halving a black/white checkerboard averages to grey
+        let checker: Vec<u8> = (0..16)
+            .flat_map(|i| {
+                let v = if (i % 4 + i / 4) % 2 == 0 { 0 } else { 255 };
+                [v, v, v, 255]
+            })
+            .collect();
+        let half = resize_rgba(&checker, 4, 4, 2, 2, ResizeFilter::Bilinear).unwrap();
+        assert!(half.chunks(4).all(|p| (120..=135).contains(&p[0])));
+    }
+
+    #[test]
+    fn test_premultiplied_alpha() {
+        // Opaque red beside transparent "green": no green fringe when scaling
+        let src = [255, 0, 0, 255, 0, 255, 0, 0];
+        for filter in &FILTERS[1..] {
+            let out = resize_rgba(&src, 2, 1, 5, 1, *filter).unwrap();
+            for px in out.chunks(4).filter(|p| p[3] > 0) {
+                assert_eq!(px[1], 0, "{filter:?}: {px:?}");
+            }
+        }
+        assert!(resize_rgba(&src, 2, 2, 1, 1, ResizeFilter::Bilinear).is_err());
+        assert!(resize_rgba(&[], 0, 0, 1, 1, ResizeFilter::Bilinear).is_err());
+        assert!(resize_rgba(&src, 2, 1, 0, 3, ResizeFilter::Bicubic)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_axis_order() {
+        // A column turned into a row: the intermediate image must be 1×1,
+        // not 70000×70000
+        let column: Vec<u8> = (0..70_000u32)
+            .flat_map(|i| [(i % 256) as u8, 0, 0, 255])
+            .collect();
+        for filter in FILTERS {
+            let row = resize_rgba(&column, 1, 70_000, 70_000, 1, filter).unwrap();
+            assert_eq!(row.len(), 70_000 * 4);
+            assert!(row.chunks(4).all(|p| p[3] == 255));
+        }
+
+        // Resizing the transpose takes the other axis order; the result
+        // must be the transpose of the original's
+        let transpose = |img: &[u8], w: usize, h: usize| -> Vec<u8> {
+            (0..w * h)
+                .flat_map(|i| {
+                    let p = ((i % h) * w + i / h) * 4;
+                    img[p..p + 4].to_vec()
+                })
+                .collect()
+        };
+        let src: Vec<u8> = (0..6 * 4)
+            .flat_map(|i| [(i % 6 * 40) as u8, (i / 6 * 60) as u8, (i * 10) as u8, 255])
+            .collect();
+        for filter in FILTERS {
+            let out = resize_rgba(&src, 6, 4, 3, 8, filter).unwrap();
+            let out_t = resize_rgba(&transpose(&src, 6, 4), 4, 6, 8, 3, filter).unwrap();
+            let diff = out
+                .iter()
+                .zip(transpose(&out_t, 8, 3))
+                .map(|(&a, b)| a.abs_diff(b))
+                .max();
+            assert!(diff <= Some(1), "{filter:?}: {diff:?}");
+        }
+        assert!(resize_rgba(&src, 6, 4, u32::MAX, u32::MAX, ResizeFilter::Bilinear).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+//! In-memory inverted index with BM25 ranking.
+//!
+//! Text is split on anything that is not alphanumeric and lowercased with
+//! Unicode rules (`str::to_lowercase`). That folds case in any script but not
+//! expansions such as "ß" vs "SS". Query terms can match exactly or as
+//! prefixes of indexed terms, which suits type-ahead search.
+//!
+//! Multiple fields are scored as one document, each field's term counts
+//! multiplied by its weight (a simplified BM25F).
+
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, try_filled, ComputeError, Result};
+
+const DEFAULT_K1: f64 = 1.2;
+const DEFAULT_B: f64 = 0.75;
+
+/// Lowercased alphanumeric runs of `text`
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Fields to index, one string per document each
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct SearchIndexBuilder {
+    len: Option<usize>,
+    fields: Vec<(Vec<String>, f64)>,
+    k1: f64,
+    b: f64,
+}
+
+impl Default for SearchIndexBuilder {
+    fn default() -> Self {
+        SearchIndexBuilder {
+            len: None,
+            fields: Vec::new(),
+            k1: DEFAULT_K1,
+            b: DEFAULT_B,
+        }
+    }
+}
+
+impl SearchIndexBuilder {
+    pub fn push_field(&mut self, values: Vec<String>, weight: f64) -> Result<()> {
+        if !(weight.is_finite() && weight > 0.0) {
+            return Err(ComputeError::invalid(format!(
+                "field weight must be positive, got {weight}"
+            )));
+        }
+        match self.len {
+            Some(expected) => check_len("search field", values.len(), expected)?,
+            None if values.len() > u32::MAX as usize => {
+                return Err(ComputeError::Overflow("document count exceeds u32 ids"))
+            }
+            None => self.len = Some(values.len()),
+        }
+        self.fields.push((values, weight));
+        Ok(())
+    }
+
+    pub fn set_bm25(&mut self, k1: f64, b: f64) -> Result<()> {
+        if !(k1 >= 0.0 && (0.0..=1.0).contains(&b)) {
+            return Err(ComputeError::invalid(format!(
+                "BM25 needs k1 >= 0 and 0 <= b <= 1, got k1 = {k1}, b = {b}"
+            )));
+        }
+        self.k1 = k1;
+        self.b = b;
+        Ok(())
+    }
+
+    pub fn build_index(self) -> Result<SearchIndex> {
+        let docs = self.len.unwrap_or(0);
+        let mut postings: HashMap<String, Vec<(u32, f32)>> = HashMap::new();
+        let mut doc_len = try_filled(docs, 0.0f32)?;
+        let mut counts: HashMap<String, f32> = HashMap::new();
+
+        // Document by document, so every posting list comes out sorted by id
+        for (doc, len) in doc_len.iter_mut().enumerate() {
+            for (values, weight) in &self.fields {
+                for token in tokenize(&values[doc]) {
+                    *counts.entry(token).or_insert(0.0) += *weight as f32;
+                    *len += *weight as f32;
+                }
+            }
+            for (term, tf) in counts.drain() {
+                postings.entry(term).or_default().push((doc as u32, tf));
+            }
+        }
+
+        let mut vocabulary: Vec<String> = postings.keys().cloned().collect();
+        vocabulary.sort_unstable();
+        let avg_len = if docs > 0 {
+            doc_len.iter().map(|&l| l as f64).sum::<f64>() / docs as f64
+        } else {
+            0.0
+        };
+
+        Ok(SearchIndex {
+            postings,
+            vocabulary,
+            doc_len,
+            avg_len,
+            k1: self.k1,
+            b: self.b,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl SearchIndexBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SearchIndexBuilder {
+        SearchIndexBuilder::default()
+    }
+
+    /// Add a field column; `weight` scales its term counts (1 for equal weight)
+    #[wasm_bindgen(js_name = addField)]
+    pub fn add_field(&mut self, values: Vec<String>, weight: f64) -> Result<(), JsError> {
+        Ok(self.push_field(values, weight)?)
+    }
+
+    /// Override the BM25 parameters (defaults k1 = 1.2, b = 0.75)
+    pub fn bm25(&mut self, k1: f64, b: f64) -> Result<(), JsError> {
+        Ok(self.set_bm25(k1, b)?)
+    }
+
+    /// Tokenize every field and build the index. The builder is consumed.
+    pub fn build(self) -> Result<SearchIndex, JsError> {
+        Ok(self.build_index()?)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct SearchIndex {
+    postings: HashMap<String, Vec<(u32, f32)>>,
+    /// Sorted terms, for prefix lookups
+    vocabulary: Vec<String>,
+    /// Weighted token count of each document
+    doc_len: Vec<f32>,
+    avg_len: f64,
+    k1: f64,
+    b: f64,
+}
+
+/// Ranked documents, best first
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHits {
+    indices: Vec<u32>,
+    scores: Vec<f64>,
+}
+
+impl SearchIndex {
+    /// Indexed terms matching `token`, exactly or by prefix
+    fn expand<'a>(&'a self, token: &'a str, prefix: bool) -> impl Iterator<Item = &'a str> + 'a {
+        let start = self.vocabulary.partition_point(|t| t.as_str() < token);
+        self.vocabulary[start..]
+            .iter()
+            .take_while(move |t| {
+                if prefix {
+                    t.starts_with(token)
+                } else {
+                    t.as_str() == token
+                }
+            })
+            .map(String::as_str)
+    }
+
+    /// Rank documents for `query`; with `all_terms` every query token must match
+    pub fn query(
+        &self,
+        query: &str,
+        limit: usize,
+        prefix: bool,
+        all_terms: bool,
+    ) -> Result<SearchHits> {
+        let docs = self.doc_len.len();
+        let n = docs as f64;
+        let mut tokens: Vec<String> = tokenize(query).collect();
+        tokens.sort_unstable();
+        tokens.dedup();
+
+        let mut scores = try_filled(docs, 0.0f64)?;
+        let mut matched = try_filled(docs, 0u32)?;
+        for (t, token) in tokens.iter().enumerate() {
+            for term in self.expand(token, prefix) {
+                let list = &self.postings[term];
+                let df = list.len() as f64;
+                let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+                for &(doc, tf) in list {
+                    let d = doc as usize;
+                    let m = matched[d];
+                    if all_terms && m != t as u32 && m != t as u32 + 1 {
+                        // Already missed an earlier token
+                        continue;
+                    }
+                    let tf = tf as f64;
+                    let norm = 1.0 - self.b + self.b * self.doc_len[d] as f64 / self.avg_len;
+                    scores[d] += idf * tf * (self.k1 + 1.0) / (tf + self.k1 * norm);
+                    // Count each query token once per document
+                    if m == t as u32 {
+                        matched[d] += 1;
+                    }
+                }
+            }
+            if all_terms {
+                // Documents that missed this token can no longer qualify
+                for (m, s) in matched.iter_mut().zip(scores.iter_mut()) {
+                    if *m != t as u32 + 1 {
+                        *m = u32::MAX;
+                        *s = 0.0;
+                    }
+                }
+            }
+        }
+
+        let hits = (0..docs as u32)
+            .filter(|&d| scores[d as usize] > 0.0)
+            .collect();
+        Ok(SearchHits::top(hits, &scores, limit))
+    }
+}
+
+#[wasm_bindgen]
+impl SearchIndex {
+    #[wasm_bindgen(getter, js_name = documentCount)]
+    pub fn document_count(&self) -> usize {
+        self.doc_len.len()
+    }
+
+    #[wasm_bindgen(getter, js_name = termCount)]
+    pub fn term_count(&self) -> usize {
+        self.vocabulary.len()
+    }
+
+    /// Top `limit` documents for `query` by BM25 score
+    ///
+    /// `prefix` lets each query token match indexed terms it is a prefix of;
+    /// `allTerms` drops documents that do not match every query token.
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        prefix: bool,
+        all_terms: bool,
+    ) -> Result<SearchHits, JsError> {
+        Ok(self.query(query, limit, prefix, all_terms)?)
+    }
+}
+
+impl SearchHits {
+    /// The best `limit` of `candidates` by `scores[candidate]`, ties by index
+    pub(crate) fn top(mut candidates: Vec<u32>, scores: &[f64], limit: usize) -> SearchHits {
+        let by_score = |a: &u32, b: &u32| {
+            scores[*b as usize]
+                .total_cmp(&scores[*a as usize])
+                .then(a.cmp(b))
+        };
+        if candidates.len() > limit && limit > 0 {
+            candidates.select_nth_unstable_by(limit - 1, by_score);
+        }
+        candidates.truncate(limit);
+        candidates.sort_unstable_by(by_score);
+
+        let hit_scores = candidates.iter().map(|&d| scores[d as usize]).collect();
+        SearchHits {
+            indices: candidates,
+            scores: hit_scores,
+        }
+    }
+
+    pub fn hit_indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn hit_scores(&self) -> &[f64] {
+        &self.scores
+    }
+}
+
+#[wasm_bindgen]
+impl SearchHits {
+    /// Document (row) indices, best match first
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn scores(&self) -> Vec<f64> {
+        self.scores.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> SearchIndex {
+        let mut builder = SearchIndexBuilder::new();
+        let titles = [
+            "Rust WebAssembly guide",
+            "Fast Rust",
+            "Cooking pasta",
+            "ÉCOLE du rust",
+        ];
+        let tags = ["wasm", "performance", "food", "français"];
+        builder
+            .push_field(titles.map(String::from).to_vec(), 2.0)
+            .unwrap();
+        builder
+            .push_field(tags.map(String::from).to_vec(), 1.0)
+            .unwrap();
+        builder.build_index().unwrap()
+    }
+
+    #[test]
+    fn test_tokenize_folds_case() {
+        let tokens: Vec<String> = tokenize("Hello, WORLD! École-42").collect();
+        assert_eq!(tokens, vec!["hello", "world", "école", "42"]);
+    }
+
+    #[test]
+    fn test_bm25_ranking() {
+        let idx = index();
+        let hits = idx.query("rust", 10, false, false).unwrap();
+        // Shorter documents rank higher for the same term frequency; ties by id
+        assert_eq!(hits.indices, vec![1, 0, 3]);
+        assert!(hits.scores.windows(2).all(|w| w[0] >= w[1]));
+
+        assert_eq!(
+            idx.query("rust wasm", 1, false, false).unwrap().indices,
+            vec![0]
+        );
+        assert_eq!(
+            idx.query("rust wasm", 10, false, true).unwrap().indices,
+            vec![0]
+        );
+        assert_eq!(
+            idx.query("école", 10, false, false).unwrap().indices,
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn test_prefix_matching() {
+        let idx = index();
+        assert!(idx
+            .query("perf", 10, false, false)
+            .unwrap()
+            .indices
+            .is_empty());
+        assert_eq!(idx.query("perf", 10, true, false).unwrap().indices, vec![1]);
+        assert_eq!(idx.query("Coo", 10, true, false).unwrap().indices, vec![2]);
+        assert!(idx.query("", 10, true, false).unwrap().indices.is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+//! In-place sorting of typed arrays.
+//!
+//! Backed by `slice::sort_unstable_by` (pattern-defeating quicksort with a
+//! heapsort fallback), so sorted or adversarial input stays O(n log n) and
+//! recursion depth stays logarithmic. Floats use a total order: NaNs are
+//! grouped at the start or end regardless of direction, and `-0.0` sorts
+//! before `0.0`.
+
+use std::cmp::Ordering;
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Ascending = 0,
+    Descending = 1,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NanPlacement {
+    First = 0,
+    #[default]
+    Last = 1,
+}
+
+/// Element types with a total order under a given direction and NaN policy
+pub trait SortKey: Copy {
+    fn compare(a: &Self, b: &Self, order: SortOrder, nans: NanPlacement) -> Ordering;
+}
+
+fn directed(ord: Ordering, order: SortOrder) -> Ordering {
+    match order {
+        SortOrder::Ascending => ord,
+        SortOrder::Descending => ord.reverse(),
+    }
+}
+
+macro_rules! int_key {
+    ($($t:ty),*) => {$(
+        impl SortKey for $t {
+            fn compare(a: &Self, b: &Self, order: SortOrder, _: NanPlacement) -> Ordering {
+                directed(a.cmp(b), order)
+            }
+        }
+    )*};
+}
+
+macro_rules! float_key {
+    ($($t:ty),*) => {$(
+        impl SortKey for $t {
+            fn compare(a: &Self, b: &Self, order: SortOrder, nans: NanPlacement) -> Ordering {
+                let nan_first = nans == NanPlacement::First;
+                match (a.is_nan(), b.is_nan()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) if nan_first => Ordering::Less,
+                    (true, false) => Ordering::Greater,
+                    (false, true) if nan_first => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) => directed(a.total_cmp(b), order),
+                }
+            }
+        }
+    )*};
+}
+
+int_key!(i32, u32);
+float_key!(f32, f64);
+
+pub fn sort<T: SortKey>(data: &mut [T], order: SortOrder, nans: NanPlacement) {
+    data.sort_unstable_by(|a, b| T::compare(a, b, order, nans));
+}
+
+/// Sort a `Float64Array` in place
+#[wasm_bindgen]
+pub fn sort_f64(data: &mut [f64], order: SortOrder, nans: NanPlacement) {
+    sort(data, order, nans);
+}
+
+/// Sort a `Float32Array` in place
+#[wasm_bindgen]
+pub fn sort_f32(data: &mut [f32], order: SortOrder, nans: NanPlacement) {
+    sort(data, order, nans);
+}
+
+/// Sort an `Int32Array` in place
+#[wasm_bindgen]
+pub fn sort_i32(data: &mut [i32], order: SortOrder) {
+    sort(data, order, NanPlacement::Last);
+}
+
+/// Sort a `Uint32Array` in place
+#[wasm_bindgen]
+pub fn sort_u32(data: &mut [u32], order: SortOrder) {
+    sort(data, order, NanPlacement::Last);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_float_nan_placement() {
+        let input = [3.0, f64::NAN, -1.0, 0.0, -0.0, f64::NAN, 2.5];
+
+        let mut a = input;
+        sort(&mut a, SortOrder::Ascending, NanPlacement::Last);
+        assert_eq!(&a[..5], &[-1.0, -0.0, 0.0, 2.5, 3.0]);
+        assert!(a[0..5].iter().all(|x| !x.is_nan()) && a[5..].iter().all(|x| x.is_nan()));
+        assert!(a[1].is_sign_negative());
+
+        let mut d = input;
+        sort(&mut d, SortOrder::Descending, NanPlacement::First);
+        assert!(d[..2].iter().all(|x| x.is_nan()));
+        assert_eq!(&d[2..], &[3.0, 2.5, 0.0, -0.0, -1.0]);
+    }
+
+    #[test]
+    fn test_integers() {
+        let mut i = [5, -3, 0, i32::MIN, i32::MAX];
+        sort(&mut i, SortOrder::Ascending, NanPlacement::Last);
+        assert_eq!(i, [i32::MIN, -3, 0, 5, i32::MAX]);
+
+        let mut u = [1u32, 4, 2];
+        sort_u32(&mut u, SortOrder::Descending);
+        assert_eq!(u, [4, 2, 1]);
+    }
+
+    #[test]
+    fn test_large_presorted_input() {
+        // The old Lomuto quicksort recursed once per element here
+        let mut data: Vec<f64> = (0..1_000_000).map(f64::from).collect();
+        sort(&mut data, SortOrder::Descending, NanPlacement::Last);
+        assert!(data.windows(2).all(|w| w[0] >= w[1]));
+    }
+}
@@ -0,0 +1,415 @@
+//! Sparse matrices in coordinate (COO) and compressed sparse row (CSR) form.
+//!
+//! COO is the construction format: triplets can arrive in any order and may
+//! repeat. CSR is the compute format: duplicates are summed, column indices
+//! are sorted within each row, and products walk each row once.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, try_filled, ComputeError, Result};
+use crate::matrix::Matrix;
+
+fn check_triplets(
+    rows: usize,
+    cols: usize,
+    row_idx: &[u32],
+    col_idx: &[u32],
+    values: &[f64],
+) -> Result<()> {
+    // Nothing is sized by `rows × cols`, so only the indptr length is checked:
+    // indptr arrays hold `rows + 1` (or `cols + 1` once transposed) entries
+    rows.max(cols)
+        .checked_add(1)
+        .ok_or(ComputeError::Overflow("matrix dimensions"))?;
+    check_len("column indices", col_idx.len(), row_idx.len())?;
+    check_len("values", values.len(), row_idx.len())?;
+    for (&r, &c) in row_idx.iter().zip(col_idx) {
+        if r as usize >= rows || c as usize >= cols {
+            return Err(ComputeError::invalid(format!(
+                "entry ({r}, {c}) out of bounds for {rows}x{cols} matrix"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Sparse matrix as unordered `(row, col, value)` triplets
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CooMatrix {
+    rows: usize,
+    cols: usize,
+    row_idx: Vec<u32>,
+    col_idx: Vec<u32>,
+    values: Vec<f64>,
+}
+
+impl CooMatrix {
+    pub fn from_triplets(
+        rows: usize,
+        cols: usize,
+        row_idx: Vec<u32>,
+        col_idx: Vec<u32>,
+        values: Vec<f64>,
+    ) -> Result<CooMatrix> {
+        check_triplets(rows, cols, &row_idx, &col_idx, &values)?;
+        Ok(CooMatrix {
+            rows,
+            cols,
+            row_idx,
+            col_idx,
+            values,
+        })
+    }
+
+    /// Compress into CSR, summing duplicate entries
+    pub fn to_csr_matrix(&self) -> Result<CsrMatrix> {
+        let mut counts = try_filled(self.rows + 1, 0usize)?;
+        for &r in &self.row_idx {
+            counts[r as usize + 1] += 1;
+        }
+        for i in 0..self.rows {
+            counts[i + 1] += counts[i];
+        }
+
+        // Counting sort by row, then sort and merge columns within each row
+        let mut order = try_filled(self.values.len(), 0usize)?;
+        let mut next = counts.clone();
+        for (i, &r) in self.row_idx.iter().enumerate() {
+            order[next[r as usize]] = i;
+            next[r as usize] += 1;
+        }
+
+        let mut indptr = Vec::with_capacity(self.rows + 1);
+        let mut indices = Vec::with_capacity(self.values.len());
+        let mut values = Vec::with_capacity(self.values.len());
+        indptr.push(0);
+        for r in 0..self.rows {
+            let row = &mut order[counts[r]..counts[r + 1]];
+            row.sort_by_key(|&i| self.col_idx[i]);
+            let start = indices.len();
+            for &i in row.iter() {
+                let c = self.col_idx[i] as usize;
+                if indices.len() > start && indices.last() == Some(&c) {
+                    *values.last_mut().unwrap() += self.values[i];
+                } else {
+                    indices.push(c);
+                    values.push(self.values[i]);
+                }
+            }
+            indptr.push(indices.len());
+        }
+
+        Ok(CsrMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            indptr,
+            indices,
+            values,
+        })
+    }
+
+    pub fn mul_vec(&self, x: &[f64]) -> Result<Vec<f64>> {
+        check_len("vector", x.len(), self.cols)?;
+        let mut y = try_filled(self.rows, 0.0)?;
+        for ((&r, &c), &v) in self.row_idx.iter().zip(&self.col_idx).zip(&self.values) {
+            y[r as usize] += v * x[c as usize];
+        }
+        Ok(y)
+    }
+
+    pub fn dense(&self) -> Result<Matrix> {
+        let mut m = Matrix::zeros(self.rows, self.cols)?;
+        for ((&r, &c), &v) in self.row_idx.iter().zip(&self.col_idx).zip(&self.values) {
+            m.data_mut()[r as usize * self.cols + c as usize] += v;
+        }
+        Ok(m)
+    }
+}
+
+#[wasm_bindgen]
+impl CooMatrix {
+    /// Build from parallel arrays of row indices, column indices and values
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        rows: usize,
+        cols: usize,
+        row_idx: Vec<u32>,
+        col_idx: Vec<u32>,
+        values: Vec<f64>,
+    ) -> Result<CooMatrix, JsError> {
+        Ok(CooMatrix::from_triplets(
+            rows, cols, row_idx, col_idx, values,
+        )?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Stored entries, counting duplicates separately
+    #[wasm_bindgen(getter)]
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Swap row and column indices
+    pub fn transpose(&self) -> CooMatrix {
+        CooMatrix {
+            rows: self.cols,
+            cols: self.rows,
+            row_idx: self.col_idx.clone(),
+            col_idx: self.row_idx.clone(),
+            values: self.values.clone(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = toCsr)]
+    pub fn to_csr(&self) -> Result<CsrMatrix, JsError> {
+        Ok(self.to_csr_matrix()?)
+    }
+
+    #[wasm_bindgen(js_name = toDense)]
+    pub fn to_dense(&self) -> Result<Matrix, JsError> {
+        Ok(self.dense()?)
+    }
+
+    /// Sparse × dense vector
+    #[wasm_bindgen(js_name = multiplyVector)]
+    pub fn multiply_vector(&self, x: &[f64]) -> Result<Vec<f64>, JsError> {
+        Ok(self.mul_vec(x)?)
+    }
+}
+
+/// Sparse matrix in compressed sparse row form
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsrMatrix {
+    rows: usize,
+    cols: usize,
+    /// Row `r` occupies `indices[indptr[r]..indptr[r + 1]]`
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl CsrMatrix {
+    fn row_range(&self, r: usize) -> std::ops::Range<usize> {
+        self.indptr[r]..self.indptr[r + 1]
+    }
+
+    pub fn mul_vec(&self, x: &[f64]) -> Result<Vec<f64>> {
+        check_len("vector", x.len(), self.cols)?;
+        let mut y = try_filled(self.rows, 0.0)?;
+        for (r, out) in y.iter_mut().enumerate() {
+            *out = self
+                .row_range(r)
+                .map(|i| self.values[i] * x[self.indices[i]])
+                .sum();
+        }
+        Ok(y)
+    }
+
+    /// Sparse × dense matrix
+    pub fn mul_dense(&self, b: &Matrix) -> Result<Matrix> {
+        check_len("right-hand matrix rows", b.rows(), self.cols)?;
+        let n = b.cols();
+        let mut c = Matrix::zeros(self.rows, n)?;
+        for r in 0..self.rows {
+            let out = &mut c.data_mut()[r * n..(r + 1) * n];
+            for i in self.row_range(r) {
+                let v = self.values[i];
+                for (o, &x) in out.iter_mut().zip(b.row(self.indices[i])) {
+                    *o += v * x;
+                }
+            }
+        }
+        Ok(c)
+    }
+
+    pub fn transposed(&self) -> Result<CsrMatrix> {
+        let mut indptr = try_filled(self.cols + 1, 0usize)?;
+        for &c in &self.indices {
+            indptr[c + 1] += 1;
+        }
+        for c in 0..self.cols {
+            indptr[c + 1] += indptr[c];
+        }
+        let mut next = indptr.clone();
+        let mut indices = try_filled(self.indices.len(), 0usize)?;
+        let mut values = try_filled(self.values.len(), 0.0)?;
+        // Visiting rows in order keeps the new column indices sorted
+        for r in 0..self.rows {
+            for i in self.row_range(r) {
+                let dst = next[self.indices[i]];
+                indices[dst] = r;
+                values[dst] = self.values[i];
+                next[self.indices[i]] += 1;
+            }
+        }
+        Ok(CsrMatrix {
+            rows: self.cols,
+            cols: self.rows,
+            indptr,
+            indices,
+            values,
+        })
+    }
+
+    pub fn dense(&self) -> Result<Matrix> {
+        let mut m = Matrix::zeros(self.rows, self.cols)?;
+        for r in 0..self.rows {
+            for i in self.row_range(r) {
+                m.data_mut()[r * self.cols + self.indices[i]] = self.values[i];
+            }
+        }
+        Ok(m)
+    }
+}
+
+#[wasm_bindgen]
+impl CsrMatrix {
+    /// Build from triplets; duplicate entries are summed
+    #[wasm_bindgen(js_name = fromTriplets)]
+    pub fn from_triplets(
+        rows: usize,
+        cols: usize,
+        row_idx: Vec<u32>,
+        col_idx: Vec<u32>,
+        values: Vec<f64>,
+    ) -> Result<CsrMatrix, JsError> {
+        Ok(CooMatrix::from_triplets(rows, cols, row_idx, col_idx, values)?.to_csr_matrix()?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Row pointer array (`rows + 1` entries)
+    #[wasm_bindgen(getter)]
+    pub fn indptr(&self) -> Vec<u32> {
+        self.indptr.iter().map(|&i| i as u32).collect()
+    }
+
+    /// Column index of each stored value
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.iter().map(|&i| i as u32).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn values(&self) -> Vec<f64> {
+        self.values.clone()
+    }
+
+    /// Sparse × dense vector
+    #[wasm_bindgen(js_name = multiplyVector)]
+    pub fn multiply_vector(&self, x: &[f64]) -> Result<Vec<f64>, JsError> {
+        Ok(self.mul_vec(x)?)
+    }
+
+    /// Sparse × dense `Matrix`
+    #[wasm_bindgen(js_name = multiplyDense)]
+    pub fn multiply_dense(&self, b: &Matrix) -> Result<Matrix, JsError> {
+        Ok(self.mul_dense(b)?)
+    }
+
+    pub fn transpose(&self) -> Result<CsrMatrix, JsError> {
+        Ok(self.transposed()?)
+    }
+
+    #[wasm_bindgen(js_name = toDense)]
+    pub fn to_dense(&self) -> Result<Matrix, JsError> {
+        Ok(self.dense()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // [1 0 2]
+    // [0 0 3]
+    fn sample() -> CooMatrix {
+        // Unordered, with the 2 at (0, 2) split into a duplicate
+        CooMatrix::from_triplets(
+            2,
+            3,
+            vec![1, 0, 0, 0],
+            vec![2, 2, 0, 2],
+            vec![3.0, 1.5, 1.0, 0.5],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_coo_to_csr() {
+        let csr = sample().to_csr_matrix().unwrap();
+        assert_eq!(csr.indptr, vec![0, 2, 3]);
+        assert_eq!(csr.indices, vec![0, 2, 2]);
+        assert_eq!(csr.values, vec![1.0, 2.0, 3.0]);
+        assert_eq!(csr.dense().unwrap(), sample().dense().unwrap());
+        assert_eq!(csr.dense().unwrap().data(), &[1.0, 0.0, 2.0, 0.0, 0.0, 3.0]);
+    }
+
+    #[test]
+    fn test_products_and_transpose() {
+        let coo = sample();
+        let csr = coo.to_csr_matrix().unwrap();
+        let x = [1.0, 2.0, 3.0];
+        assert_eq!(csr.mul_vec(&x).unwrap(), vec![7.0, 9.0]);
+        assert_eq!(coo.mul_vec(&x).unwrap(), vec![7.0, 9.0]);
+
+        let b = Matrix::from_vec(vec![1.0, 0.0, 0.0, 1.0, 1.0, 1.0], 3, 2).unwrap();
+        let dense = csr.dense().unwrap();
+        assert_eq!(csr.mul_dense(&b).unwrap(), dense.matmul(&b).unwrap());
+
+        let t = csr.transposed().unwrap();
+        assert_eq!(t.dense().unwrap(), dense.transposed().unwrap());
+        assert_eq!(t.dense().unwrap(), coo.transpose().dense().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_triplets() {
+        assert!(CooMatrix::from_triplets(2, 2, vec![2], vec![0], vec![1.0]).is_err());
+        assert!(CooMatrix::from_triplets(2, 2, vec![0, 1], vec![0], vec![1.0]).is_err());
+        assert!(sample().to_csr_matrix().unwrap().mul_vec(&[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_dense_size_beyond_address_space() {
+        // 10¹⁰ dense entries would not fit a wasm32 address space
+        let n = 100_000;
+        let coo = CooMatrix::from_triplets(
+            n,
+            n,
+            vec![0, 99_999, 50_000],
+            vec![99_999, 0, 50_000],
+            vec![1.0, 2.0, 3.0],
+        )
+        .unwrap();
+        let csr = coo.to_csr_matrix().unwrap();
+        assert_eq!((csr.rows(), csr.cols(), csr.nnz()), (n, n, 3));
+        let x: Vec<f64> = (0..n).map(|i| i as f64).collect();
+        let y = csr.mul_vec(&x).unwrap();
+        assert_eq!((y[0], y[50_000], y[99_999]), (99_999.0, 150_000.0, 0.0));
+        assert_eq!(csr.transposed().unwrap().mul_vec(&x).unwrap()[0], 199_998.0);
+    }
+}
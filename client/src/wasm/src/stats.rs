@@ -0,0 +1,375 @@
+//! Descriptive statistics for `f64` samples.
+//!
+//! NaNs are treated as missing: they are counted and skipped, as in
+//! `groupby` aggregations. Sums use Neumaier's compensated summation, and
+//! the central moments are taken in a second pass over the deviations from
+//! the mean (with the usual correction term), which stays accurate where the
+//! one-pass `Σx² - n·mean²` formula cancels catastrophically.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{ComputeError, Result};
+use crate::summation::Neumaier;
+
+/// How a quantile falling between two order statistics is resolved; the
+/// names and definitions follow NumPy's `percentile`
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuantileMethod {
+    /// Linear interpolation between the neighbours (R type 7)
+    #[default]
+    Linear = 0,
+    Lower = 1,
+    Higher = 2,
+    /// Closer neighbour, ties to the even index
+    Nearest = 3,
+    Midpoint = 4,
+}
+
+/// Location of quantile `p` among `n` sorted values: the neighbouring
+/// indices and the fraction of the way from one to the other
+fn position(n: usize, p: f64) -> (usize, usize, f64) {
+    let h = (n - 1) as f64 * p;
+    let lo = h.floor() as usize;
+    (lo, h.ceil() as usize, h - lo as f64)
+}
+
+fn interpolate(lower: f64, higher: f64, frac: f64, lo: usize, method: QuantileMethod) -> f64 {
+    match method {
+        // Exact hits must not compute inf - inf
+        _ if frac == 0.0 => lower,
+        QuantileMethod::Linear => lower + frac * (higher - lower),
+        QuantileMethod::Lower => lower,
+        QuantileMethod::Higher => higher,
+        QuantileMethod::Nearest => {
+            if (lo as f64 + frac).round_ties_even() == lo as f64 {
+                lower
+            } else {
+                higher
+            }
+        }
+        QuantileMethod::Midpoint => (lower + higher) / 2.0,
+    }
+}
+
+fn check_probability(p: f64) -> Result<()> {
+    if (0.0..=1.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(ComputeError::invalid(format!(
+            "quantile probability {p} is outside [0, 1]"
+        )))
+    }
+}
+
+/// The non-NaN values of `data`
+fn present(data: &[f64]) -> Result<Vec<f64>> {
+    let mut v = Vec::new();
+    v.try_reserve_exact(data.len())
+        .map_err(|_| ComputeError::AllocationFailure {
+            bytes: data.len() * 8,
+        })?;
+    v.extend(data.iter().copied().filter(|x| !x.is_nan()));
+    Ok(v)
+}
+
+/// Quantile `p` of the NaN-free `v` by selection, in `O(n)`; reorders `v`
+fn select_quantile(v: &mut [f64], p: f64, method: QuantileMethod) -> f64 {
+    if v.is_empty() {
+        return f64::NAN;
+    }
+    let (lo, hi, frac) = position(v.len(), p);
+    let (_, &mut lower, rest) = v.select_nth_unstable_by(lo, f64::total_cmp);
+    let higher = if hi == lo {
+        lower
+    } else {
+        rest.iter().copied().fold(f64::INFINITY, f64::min)
+    };
+    interpolate(lower, higher, frac, lo, method)
+}
+
+/// Quantiles of `data` at each of `probs` (in `[0, 1]`), ignoring NaNs;
+/// NaN for each when no values are present
+pub fn quantiles_f64(data: &[f64], probs: &[f64], method: QuantileMethod) -> Result<Vec<f64>> {
+    for &p in probs {
+        check_probability(p)?;
+    }
+    let mut v = present(data)?;
+    if let [p] = probs {
+        return Ok(vec![select_quantile(&mut v, *p, method)]);
+    }
+    if v.is_empty() {
+        return Ok(vec![f64::NAN; probs.len()]);
+    }
+    v.sort_unstable_by(f64::total_cmp);
+    Ok(probs
+        .iter()
+        .map(|&p| {
+            let (lo, hi, frac) = position(v.len(), p);
+            interpolate(v[lo], v[hi], frac, lo, method)
+        })
+        .collect())
+}
+
+/// Median ignoring NaNs (the mean of the middle pair for even counts)
+pub fn median_f64(data: &[f64]) -> Result<f64> {
+    Ok(select_quantile(
+        &mut present(data)?,
+        0.5,
+        QuantileMethod::Linear,
+    ))
+}
+
+/// Moment summary of a sample, NaNs excluded
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    count: usize,
+    nan_count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    variance: f64,
+    skewness: f64,
+    kurtosis: f64,
+}
+
+impl Summary {
+    /// Summarize `data`; the variance divides by `count - ddof` (1 for the
+    /// sample variance, 0 for the population variance) and is NaN when that
+    /// is not positive. Everything but the counts is NaN for empty input.
+    pub fn compute(data: &[f64], ddof: u32) -> Summary {
+        let mut count = 0;
+        let mut sum = Neumaier::default();
+        let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+        for &x in data {
+            if x.is_nan() {
+                continue;
+            }
+            count += 1;
+            sum.add(x);
+            min = min.min(x);
+            max = max.max(x);
+        }
+        let nan_count = data.len() - count;
+        if count == 0 {
+            return Summary {
+                count,
+                nan_count,
+                sum: 0.0,
+                min: f64::NAN,
+                max: f64::NAN,
+                mean: f64::NAN,
+                variance: f64::NAN,
+                skewness: f64::NAN,
+                kurtosis: f64::NAN,
+            };
+        }
+
+        let n = count as f64;
+        let sum = sum.total();
+        let mut mean = sum / n;
+        if mean.is_infinite() && min.is_finite() && max.is_finite() {
+            // The total overflowed but the mean itself is representable
+            let mut scaled = Neumaier::default();
+            data.iter()
+                .filter(|x| !x.is_nan())
+                .for_each(|&x| scaled.add(x / n));
+            mean = scaled.total();
+        }
+
+        // Second pass: central moments. Σd would be exactly 0 in exact
+        // arithmetic; subtracting (Σd)²/n corrects for the rounding in `mean`.
+        let mut moments = [Neumaier::default(); 4];
+        for &x in data.iter().filter(|x| !x.is_nan()) {
+            let d = x - mean;
+            let d2 = d * d;
+            moments[0].add(d);
+            moments[1].add(d2);
+            moments[2].add(d2 * d);
+            moments[3].add(d2 * d2);
+        }
+        let [m1, m2, m3, m4] = moments.map(|m| m.total() / n);
+        let m2 = (m2 - m1 * m1).max(0.0);
+        let denominator = n - f64::from(ddof);
+        let variance = if denominator > 0.0 {
+            m2 * n / denominator
+        } else {
+            f64::NAN
+        };
+
+        Summary {
+            count,
+            nan_count,
+            sum,
+            min,
+            max,
+            mean,
+            variance,
+            // Fisher-Pearson g₁ and excess kurtosis g₂ (population moments);
+            // both NaN for a constant sample
+            skewness: m3 / m2.powf(1.5),
+            kurtosis: m4 / (m2 * m2) - 3.0,
+        }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+}
+
+#[wasm_bindgen]
+impl Summary {
+    /// Number of non-NaN values
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    #[wasm_bindgen(getter, js_name = nanCount)]
+    pub fn nan_count(&self) -> usize {
+        self.nan_count
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn variance(&self) -> f64 {
+        self.variance
+    }
+
+    #[wasm_bindgen(getter, js_name = stdDev)]
+    pub fn js_std_dev(&self) -> f64 {
+        self.std_dev()
+    }
+
+    /// Fisher-Pearson skewness g₁
+    #[wasm_bindgen(getter)]
+    pub fn skewness(&self) -> f64 {
+        self.skewness
+    }
+
+    /// Excess kurtosis (0 for a normal distribution)
+    #[wasm_bindgen(getter)]
+    pub fn kurtosis(&self) -> f64 {
+        self.kurtosis
+    }
+}
+
+/// Count, NaN count, sum, min, max, mean, variance (divisor `n - ddof`),
+/// standard deviation, skewness and kurtosis in one call
+#[wasm_bindgen]
+pub fn describe(data: &[f64], ddof: u32) -> Summary {
+    Summary::compute(data, ddof)
+}
+
+/// Quantiles at each probability in `probs` (`0.5` is the median), NaNs
+/// ignored
+#[wasm_bindgen]
+pub fn quantiles(data: &[f64], probs: &[f64], method: QuantileMethod) -> Result<Vec<f64>, JsError> {
+    Ok(quantiles_f64(data, probs, method)?)
+}
+
+#[wasm_bindgen]
+pub fn median(data: &[f64]) -> Result<f64, JsError> {
+    Ok(median_f64(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tol: f64) -> bool {
+        (a - b).abs() <= tol * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_summary() {
+        let s = Summary::compute(&[2.0, 4.0, f64::NAN, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], 0);
+        assert_eq!((s.count(), s.nan_count()), (8, 1));
+        assert_eq!((s.sum(), s.min(), s.max(), s.mean()), (40.0, 2.0, 9.0, 5.0));
+        assert_eq!(s.variance(), 4.0);
+        assert_eq!(s.std_dev(), 2.0);
+        assert!(close(s.skewness(), 0.65625, 1e-12));
+        assert!(close(s.kurtosis(), -0.21875, 1e-12));
+        assert_eq!(Summary::compute(&[2.0, 4.0, 6.0], 1).variance(), 4.0);
+
+        assert!(Summary::compute(&[3.0], 1).variance().is_nan());
+        let empty = Summary::compute(&[f64::NAN], 1);
+        assert_eq!((empty.count(), empty.nan_count(), empty.sum()), (0, 1, 0.0));
+        assert!(empty.mean().is_nan() && empty.min().is_nan());
+
+        // The total overflows, the mean does not
+        assert_eq!(Summary::compute(&[f64::MAX, f64::MAX], 0).mean(), f64::MAX);
+    }
+
+    #[test]
+    fn test_numerical_stability() {
+        // Large offset, tiny spread: the naive Σx² formula returns garbage
+        let data: Vec<f64> = (0..1_000_000)
+            .map(|i| 1e9 + [4.0, 7.0, 13.0, 16.0][i % 4])
+            .collect();
+        let s = Summary::compute(&data, 0);
+        assert_eq!(s.mean(), 1e9 + 10.0);
+        assert!(close(s.variance(), 22.5, 1e-9));
+        assert!(s.skewness().abs() < 1e-9);
+
+        // Neumaier keeps the small terms that naive summation drops
+        let data = [1.0, 1e100, 1.0, -1e100];
+        assert_eq!(data.iter().sum::<f64>(), 0.0);
+        assert_eq!(Summary::compute(&data, 0).sum(), 2.0);
+    }
+
+    #[test]
+    fn test_quantiles() {
+        let data = [f64::NAN, 1.0, 2.0, 3.0, 4.0];
+        let probs = [0.0, 0.4, 0.5, 0.625, 1.0];
+        let expected = [
+            (QuantileMethod::Linear, [1.0, 2.2, 2.5, 2.875, 4.0]),
+            (QuantileMethod::Lower, [1.0, 2.0, 2.0, 2.0, 4.0]),
+            (QuantileMethod::Higher, [1.0, 3.0, 3.0, 3.0, 4.0]),
+            (QuantileMethod::Nearest, [1.0, 2.0, 3.0, 3.0, 4.0]),
+            (QuantileMethod::Midpoint, [1.0, 2.5, 2.5, 2.5, 4.0]),
+        ];
+        for (method, values) in expected {
+            let got = quantiles_f64(&data, &probs, method).unwrap();
+            assert!(
+                got.iter().zip(values).all(|(&a, b)| close(a, b, 1e-12)),
+                "{method:?}: {got:?}"
+            );
+            // The single-probability path selects instead of sorting
+            for (&p, v) in probs.iter().zip(values) {
+                assert!(close(
+                    quantiles_f64(&data, &[p], method).unwrap()[0],
+                    v,
+                    1e-12
+                ));
+            }
+        }
+
+        assert_eq!(median_f64(&[5.0, 1.0, 3.0]).unwrap(), 3.0);
+        assert_eq!(median_f64(&[4.0, 1.0, 3.0, 2.0]).unwrap(), 2.5);
+        assert!(median_f64(&[]).unwrap().is_nan());
+        assert!(quantiles_f64(&data, &[1.5], QuantileMethod::Linear).is_err());
+        assert!(quantiles_f64(&data, &[f64::NAN], QuantileMethod::Linear).is_err());
+    }
+}
@@ -0,0 +1,525 @@
+//! Floating point summation with a choice of accuracy/speed trade-off.
+//!
+//! Every method is a streaming `Accumulator`, so `sum`, `dot`, `norm` and
+//! `cumsum` share the same kernels. Pairwise summation is done in streaming
+//! form: 128-element leaves are summed directly and merged like a binary
+//! counter, which gives the `O(log n)` error growth of a balanced tree
+//! without recursion. The exact method keeps Shewchuk's non-overlapping
+//! partials (as Python's `math.fsum`) and rounds their total once.
+//!
+//! The naive leaf sum and dot product are the only loops that differ between
+//! the scalar build and the `simd` feature on `wasm32` with `simd128`; the
+//! SIMD versions keep two lanes, so naive results can differ in the last
+//! bits between the two builds. The compensated and exact methods are
+//! scalar.
+
+use std::ops::Range;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, try_filled, ComputeError, Result};
+
+/// Values per pairwise leaf, summed directly
+const LEAF: usize = 128;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SumMethod {
+    /// Left-to-right accumulation; fastest, error grows with `n`
+    Naive = 0,
+    /// Balanced tree of partial sums; error grows with `log n`
+    #[default]
+    Pairwise = 1,
+    /// Kahan's compensated summation
+    Kahan = 2,
+    /// Kahan summation corrected for addends larger than the running total
+    Neumaier = 3,
+    /// Correctly rounded sum of the exact total
+    Exact = 4,
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+fn block_sum(x: &[f64]) -> f64 {
+    x.iter().sum()
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+fn block_dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+use simd::{block_dot, block_sum};
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+mod simd {
+    use core::arch::wasm32::*;
+
+    fn lanes(v: v128) -> f64 {
+        f64x2_extract_lane::<0>(v) + f64x2_extract_lane::<1>(v)
+    }
+
+    pub(super) fn block_sum(x: &[f64]) -> f64 {
+        let mut acc = f64x2_splat(0.0);
+        let mut i = 0;
+        while i + 2 <= x.len() {
+            // SAFETY: i + 2 <= len keeps the 16-byte load in bounds;
+            // v128_load has no alignment requirement.
+            unsafe {
+                acc = f64x2_add(acc, v128_load(x.as_ptr().add(i) as *const v128));
+            }
+            i += 2;
+        }
+        lanes(acc) + x[i..].iter().sum::<f64>()
+    }
+
+    pub(super) fn block_dot(a: &[f64], b: &[f64]) -> f64 {
+        let len = a.len().min(b.len());
+        let mut acc = f64x2_splat(0.0);
+        let mut i = 0;
+        while i + 2 <= len {
+            // SAFETY: as above, for both slices
+            unsafe {
+                let pa = v128_load(a.as_ptr().add(i) as *const v128);
+                let pb = v128_load(b.as_ptr().add(i) as *const v128);
+                acc = f64x2_add(acc, f64x2_mul(pa, pb));
+            }
+            i += 2;
+        }
+        lanes(acc) + (i..len).map(|j| a[j] * b[j]).sum::<f64>()
+    }
+}
+
+/// Compensated running sum (Neumaier's variant of Kahan summation, which
+/// also handles addends larger than the running total)
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Neumaier {
+    sum: f64,
+    compensation: f64,
+}
+
+impl Neumaier {
+    pub(crate) fn add(&mut self, x: f64) {
+        let t = self.sum + x;
+        if self.sum.abs() >= x.abs() {
+            self.compensation += (self.sum - t) + x;
+        } else {
+            self.compensation += (x - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    pub(crate) fn total(&self) -> f64 {
+        // Once the sum is infinite or NaN the compensation is meaningless
+        if self.sum.is_finite() {
+            self.sum + self.compensation
+        } else {
+            self.sum
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Kahan {
+    sum: f64,
+    compensation: f64,
+}
+
+impl Kahan {
+    fn add(&mut self, x: f64) {
+        let y = x - self.compensation;
+        let t = self.sum + y;
+        // An infinite total would turn the compensation into NaN
+        self.compensation = if t.is_finite() {
+            (t - self.sum) - y
+        } else {
+            0.0
+        };
+        self.sum = t;
+    }
+}
+
+/// Streaming pairwise sum: `stack` holds `(level, sum)` of complete subtrees
+/// of `2^level` leaves, levels strictly decreasing towards the top
+#[derive(Debug, Clone, Default)]
+struct Pairwise {
+    leaf: f64,
+    leaf_len: usize,
+    stack: Vec<(u32, f64)>,
+}
+
+impl Pairwise {
+    fn add(&mut self, x: f64) {
+        self.leaf += x;
+        self.leaf_len += 1;
+        if self.leaf_len == LEAF {
+            let leaf = std::mem::take(&mut self.leaf);
+            self.leaf_len = 0;
+            self.push_leaf(leaf);
+        }
+    }
+
+    /// Merge a complete leaf; only valid between leaves (`leaf_len == 0`)
+    fn push_leaf(&mut self, mut sum: f64) {
+        let mut level = 0;
+        while let Some(&(top, s)) = self.stack.last() {
+            if top != level {
+                break;
+            }
+            self.stack.pop();
+            sum += s;
+            level += 1;
+        }
+        self.stack.push((level, sum));
+    }
+
+    /// Add `len` values, given one at a time by `item` or a whole leaf at a
+    /// time by `leaf`
+    fn extend(
+        &mut self,
+        len: usize,
+        item: impl Fn(usize) -> f64,
+        leaf: impl Fn(Range<usize>) -> f64,
+    ) {
+        let mut i = 0;
+        while i < len && self.leaf_len != 0 {
+            self.add(item(i));
+            i += 1;
+        }
+        while i + LEAF <= len {
+            self.push_leaf(leaf(i..i + LEAF));
+            i += LEAF;
+        }
+        for j in i..len {
+            self.add(item(j));
+        }
+    }
+
+    fn total(&self) -> f64 {
+        // Smallest subtrees first
+        self.stack
+            .iter()
+            .rev()
+            .fold(self.leaf, |acc, &(_, s)| acc + s)
+    }
+}
+
+/// Shewchuk's exact summation: `partials` are non-overlapping and increasing
+/// in magnitude, and sum exactly to the finite inputs seen so far
+#[derive(Debug, Clone, Default)]
+struct Exact {
+    partials: Vec<f64>,
+    /// Sum of the non-finite inputs, and of any intermediate overflow
+    special: f64,
+}
+
+impl Exact {
+    fn add(&mut self, mut x: f64) {
+        if !x.is_finite() {
+            self.special += x;
+            return;
+        }
+        let mut kept = 0;
+        for j in 0..self.partials.len() {
+            let mut y = self.partials[j];
+            if x.abs() < y.abs() {
+                std::mem::swap(&mut x, &mut y);
+            }
+            let hi = x + y;
+            let lo = y - (hi - x);
+            if lo != 0.0 {
+                self.partials[kept] = lo;
+                kept += 1;
+            }
+            x = hi;
+        }
+        self.partials.truncate(kept);
+        if x.is_finite() {
+            self.partials.push(x);
+        } else {
+            self.special += x;
+        }
+    }
+
+    fn total(&self) -> f64 {
+        // Also taken for NaN
+        if self.special != 0.0 {
+            return self.special;
+        }
+        let p = &self.partials;
+        let Some(mut n) = p.len().checked_sub(1) else {
+            return 0.0;
+        };
+        // Add from the top until the sum becomes inexact
+        let (mut hi, mut lo) = (p[n], 0.0);
+        while n > 0 {
+            n -= 1;
+            let x = hi;
+            hi = x + p[n];
+            lo = p[n] - (hi - x);
+            if lo != 0.0 {
+                break;
+            }
+        }
+        // Round half to even using the sign of the next partial down
+        if n > 0 && ((lo < 0.0 && p[n - 1] < 0.0) || (lo > 0.0 && p[n - 1] > 0.0)) {
+            let y = lo * 2.0;
+            let x = hi + y;
+            if y == x - hi {
+                hi = x;
+            }
+        }
+        hi
+    }
+}
+
+/// Running total for one [`SumMethod`]
+#[derive(Debug, Clone)]
+enum Accumulator {
+    Naive(f64),
+    Pairwise(Pairwise),
+    Kahan(Kahan),
+    Neumaier(Neumaier),
+    Exact(Exact),
+}
+
+impl Accumulator {
+    fn new(method: SumMethod) -> Accumulator {
+        match method {
+            SumMethod::Naive => Accumulator::Naive(0.0),
+            SumMethod::Pairwise => Accumulator::Pairwise(Pairwise::default()),
+            SumMethod::Kahan => Accumulator::Kahan(Kahan::default()),
+            SumMethod::Neumaier => Accumulator::Neumaier(Neumaier::default()),
+            SumMethod::Exact => Accumulator::Exact(Exact::default()),
+        }
+    }
+
+    fn add(&mut self, x: f64) {
+        match self {
+            Accumulator::Naive(sum) => *sum += x,
+            Accumulator::Pairwise(p) => p.add(x),
+            Accumulator::Kahan(k) => k.add(x),
+            Accumulator::Neumaier(n) => n.add(x),
+            Accumulator::Exact(e) => e.add(x),
+        }
+    }
+
+    fn add_slice(&mut self, x: &[f64]) {
+        match self {
+            Accumulator::Naive(sum) => *sum += block_sum(x),
+            Accumulator::Pairwise(p) => p.extend(x.len(), |i| x[i], |r| block_sum(&x[r])),
+            _ => x.iter().for_each(|&x| self.add(x)),
+        }
+    }
+
+    /// Add `a[i] * b[i]` for every `i`. The compensated and exact methods
+    /// also add each product's rounding error (recovered with a fused
+    /// multiply-add), so they see the products exactly.
+    fn add_products(&mut self, a: &[f64], b: &[f64]) {
+        match self {
+            Accumulator::Naive(sum) => *sum += block_dot(a, b),
+            Accumulator::Pairwise(p) => p.extend(
+                a.len(),
+                |i| a[i] * b[i],
+                |r| block_dot(&a[r.clone()], &b[r]),
+            ),
+            _ => {
+                for (&a, &b) in a.iter().zip(b) {
+                    let p = a * b;
+                    self.add(p);
+                    let err = a.mul_add(b, -p);
+                    if err.is_finite() {
+                        self.add(err);
+                    }
+                }
+            }
+        }
+    }
+
+    fn total(&self) -> f64 {
+        match self {
+            Accumulator::Naive(sum) => *sum,
+            Accumulator::Pairwise(p) => p.total(),
+            Accumulator::Kahan(k) => k.sum,
+            Accumulator::Neumaier(n) => n.total(),
+            Accumulator::Exact(e) => e.total(),
+        }
+    }
+}
+
+/// `Overflow` for an infinite result computed from finite inputs
+fn check_finite(result: f64, inputs: &[&[f64]], what: &'static str) -> Result<f64> {
+    if result.is_infinite() && inputs.iter().all(|x| x.iter().all(|x| x.is_finite())) {
+        return Err(ComputeError::Overflow(what));
+    }
+    Ok(result)
+}
+
+pub fn sum(data: &[f64], method: SumMethod) -> Result<f64> {
+    let mut acc = Accumulator::new(method);
+    acc.add_slice(data);
+    check_finite(acc.total(), &[data], "sum exceeds f64 range")
+}
+
+pub fn dot_f64(a: &[f64], b: &[f64], method: SumMethod) -> Result<f64> {
+    check_len("b", b.len(), a.len())?;
+    let mut acc = Accumulator::new(method);
+    acc.add_products(a, b);
+    check_finite(acc.total(), &[a, b], "dot product exceeds f64 range")
+}
+
+/// Euclidean norm. Values are scaled by a power of two (exactly) so that the
+/// squares neither overflow nor underflow unless the norm itself does.
+pub fn norm_f64(data: &[f64], method: SumMethod) -> Result<f64> {
+    let max = data.iter().fold(0.0f64, |m, x| m.max(x.abs()));
+    if data.iter().any(|x| x.is_nan()) {
+        return Ok(f64::NAN);
+    }
+    if max == 0.0 || max.is_infinite() {
+        return Ok(max);
+    }
+    // 2^exponent(max), exact for normal and subnormal max alike
+    let exponent = ((max.to_bits() >> 52) as i32 - 1023).max(-1022);
+    let inverse = 2f64.powi(-exponent);
+
+    let mut acc = Accumulator::new(method);
+    let mut scaled = [0.0; LEAF];
+    for chunk in data.chunks(LEAF) {
+        let scaled = &mut scaled[..chunk.len()];
+        for (s, &x) in scaled.iter_mut().zip(chunk) {
+            *s = x * inverse;
+        }
+        acc.add_products(scaled, scaled);
+    }
+    let norm = acc.total().sqrt() * 2f64.powi(exponent);
+    check_finite(norm, &[data], "norm exceeds f64 range")
+}
+
+/// Running totals: element `i` is the sum of `data[..=i]`
+pub fn cumsum_f64(data: &[f64], method: SumMethod) -> Result<Vec<f64>> {
+    let mut out = try_filled(data.len(), 0.0)?;
+    let mut acc = Accumulator::new(method);
+    for (o, &x) in out.iter_mut().zip(data) {
+        acc.add(x);
+        *o = acc.total();
+    }
+    if out.iter().any(|x| x.is_infinite()) && data.iter().all(|x| x.is_finite()) {
+        return Err(ComputeError::Overflow("running sum exceeds f64 range"));
+    }
+    Ok(out)
+}
+
+/// Dot product of two equal-length arrays (`method` defaults to pairwise)
+#[wasm_bindgen]
+pub fn dot(a: &[f64], b: &[f64], method: Option<SumMethod>) -> Result<f64, JsError> {
+    Ok(dot_f64(a, b, method.unwrap_or_default())?)
+}
+
+/// Euclidean (L2) norm, safe from intermediate overflow and underflow
+#[wasm_bindgen]
+pub fn norm(data: &[f64], method: Option<SumMethod>) -> Result<f64, JsError> {
+    Ok(norm_f64(data, method.unwrap_or_default())?)
+}
+
+/// Cumulative sums as a new `Float64Array`
+#[wasm_bindgen]
+pub fn cumsum(data: &[f64], method: Option<SumMethod>) -> Result<Vec<f64>, JsError> {
+    Ok(cumsum_f64(data, method.unwrap_or_default())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METHODS: [SumMethod; 5] = [
+        SumMethod::Naive,
+        SumMethod::Pairwise,
+        SumMethod::Kahan,
+        SumMethod::Neumaier,
+        SumMethod::Exact,
+    ];
+
+    #[test]
+    fn test_sum_methods() {
+        let small: Vec<f64> = (1..=1000).map(f64::from).collect();
+        for method in METHODS {
+            assert_eq!(sum(&small, method).unwrap(), 500_500.0, "{method:?}");
+            assert_eq!(sum(&[], method).unwrap(), 0.0);
+            assert_eq!(sum(&[1.0, f64::INFINITY], method).unwrap(), f64::INFINITY);
+            assert!(sum(&[f64::INFINITY, f64::NEG_INFINITY], method)
+                .unwrap()
+                .is_nan());
+            assert!(matches!(
+                sum(&[f64::MAX, f64::MAX], method),
+                Err(ComputeError::Overflow(_))
+            ));
+        }
+
+        // 0.1 ten million times: the naive error is around 1e-4
+        let tenths = vec![0.1; 10_000_000];
+        let err = |m| (sum(&tenths, m).unwrap() - 1e6).abs();
+        assert!(err(SumMethod::Naive) > 1e-6);
+        assert!(err(SumMethod::Pairwise) < 1e-8);
+        assert!(err(SumMethod::Kahan) < 1e-9);
+        assert_eq!(sum(&tenths, SumMethod::Exact).unwrap(), 1e6);
+
+        // Large cancelling terms: only Neumaier and the exact sum survive
+        let data = [1.0, 1e100, 1.0, -1e100];
+        assert_eq!(sum(&data, SumMethod::Kahan).unwrap(), 0.0);
+        assert_eq!(sum(&data, SumMethod::Neumaier).unwrap(), 2.0);
+        assert_eq!(sum(&data, SumMethod::Exact).unwrap(), 2.0);
+        // Correct rounding needs the half-even fix-up at the end
+        assert_eq!(
+            sum(&[1e16, 1.0, 1e-16], SumMethod::Exact).unwrap(),
+            10_000_000_000_000_002.0
+        );
+    }
+
+    #[test]
+    fn test_pairwise_streaming() {
+        // Leaves assembled across calls match one call over the whole slice
+        let data: Vec<f64> = (0..1000).map(|i| (i as f64).sin()).collect();
+        let mut split = Accumulator::new(SumMethod::Pairwise);
+        split.add_slice(&data[..77]);
+        split.add_slice(&data[77..600]);
+        data[600..].iter().for_each(|&x| split.add(x));
+        assert_eq!(split.total(), sum(&data, SumMethod::Pairwise).unwrap());
+        assert!((split.total() - sum(&data, SumMethod::Exact).unwrap()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_dot_norm_cumsum() {
+        let a = [1.0, 2.0, 3.0];
+        let b = [4.0, -5.0, 6.0];
+        for method in METHODS {
+            assert_eq!(dot_f64(&a, &b, method).unwrap(), 12.0);
+            assert_eq!(norm_f64(&[3.0, 4.0], method).unwrap(), 5.0);
+            assert_eq!(cumsum_f64(&a, method).unwrap(), [1.0, 3.0, 6.0]);
+        }
+        assert!(dot_f64(&a, &b[..2], SumMethod::Naive).is_err());
+
+        // Product rounding errors are kept by the compensated methods
+        let x = 1.0 + f64::EPSILON;
+        let a = [x, -1.0];
+        let b = [x, 1.0 + 2.0 * f64::EPSILON];
+        assert_eq!(dot_f64(&a, &b, SumMethod::Naive).unwrap(), 0.0);
+        assert_eq!(
+            dot_f64(&a, &b, SumMethod::Exact).unwrap(),
+            f64::EPSILON * f64::EPSILON
+        );
+
+        // Neither the squares of huge nor of tiny values are representable
+        for (data, expected) in [([3e200, 4e200], 5e200), ([3e-200, 4e-200], 5e-200)] {
+            for method in METHODS {
+                let n = norm_f64(&data, method).unwrap();
+                assert!((n - expected).abs() <= 1e-15 * expected, "{method:?}: {n}");
+            }
+        }
+        assert!(norm_f64(&[f64::MAX, f64::MAX], SumMethod::Exact).is_err());
+        assert_eq!(norm_f64(&[], SumMethod::Naive).unwrap(), 0.0);
+
+        let cs = cumsum_f64(&[1e100, 1.0, -1e100], SumMethod::Neumaier).unwrap();
+        assert_eq!(cs, [1e100, 1e100, 1.0]);
+        assert!(cumsum_f64(&[f64::MAX, f64::MAX, -f64::MAX], SumMethod::Exact).is_err());
+    }
+}
@@ -0,0 +1,702 @@
+//! Columnar tables produced by the CSV and JSON readers.
+//!
+//! Each column has one inferred type. Numbers stay in typed buffers that JS
+//! receives as `Float64Array`/`BigInt64Array`; strings are dictionary encoded
+//! (`Uint32Array` codes into a deduplicated string list). Missing values are
+//! tracked in a validity mask.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, ComputeError, Result};
+
+/// Dictionary code of a null text value
+pub const NULL_CODE: u32 = u32::MAX;
+
+/// Most source texts a [`ColumnBuilder`] keeps for rows whose text cannot be
+/// re-derived from their value
+const MAX_RAW_FIELDS: usize = 1 << 16;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    /// No non-null values
+    Null = 0,
+    Boolean = 1,
+    /// 64-bit signed integers
+    Integer = 2,
+    Float = 3,
+    Text = 4,
+}
+
+impl ColumnType {
+    /// Narrowest type that can hold values of both `self` and `other`
+    fn join(self, other: ColumnType) -> ColumnType {
+        use ColumnType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Null, t) | (t, Null) => t,
+            (Integer, Float) | (Float, Integer) => Float,
+            _ => Text,
+        }
+    }
+}
+
+/// One value handed to a [`ColumnBuilder`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cell<'a> {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    Text(&'a str),
+}
+
+impl Cell<'_> {
+    fn column_type(&self) -> ColumnType {
+        match self {
+            Cell::Null => ColumnType::Null,
+            Cell::Boolean(_) => ColumnType::Boolean,
+            Cell::Integer(_) => ColumnType::Integer,
+            Cell::Float(_) => ColumnType::Float,
+            Cell::Text(_) => ColumnType::Text,
+        }
+    }
+}
+
+/// Column values; slots of null rows hold `false`, `0` or [`NULL_CODE`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnData {
+    Null,
+    Boolean(Vec<bool>),
+    Integer(Vec<i64>),
+    Float(Vec<f64>),
+    Text {
+        codes: Vec<u32>,
+        dictionary: Vec<String>,
+    },
+}
+
+impl ColumnData {
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            ColumnData::Null => ColumnType::Null,
+            ColumnData::Boolean(_) => ColumnType::Boolean,
+            ColumnData::Integer(_) => ColumnType::Integer,
+            ColumnData::Float(_) => ColumnType::Float,
+            ColumnData::Text { .. } => ColumnType::Text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    name: String,
+    data: ColumnData,
+    valid: Vec<bool>,
+    null_count: usize,
+}
+
+impl Column {
+    /// Column from values and a validity mask (`true` = present)
+    pub fn new(name: String, data: ColumnData, valid: Vec<bool>) -> Result<Column> {
+        let len = valid.len();
+        match &data {
+            ColumnData::Null if valid.iter().any(|&v| v) => {
+                return Err(ComputeError::invalid("null column with valid rows"))
+            }
+            ColumnData::Null => {}
+            ColumnData::Boolean(v) => check_len("column values", v.len(), len)?,
+            ColumnData::Integer(v) => check_len("column values", v.len(), len)?,
+            ColumnData::Float(v) => check_len("column values", v.len(), len)?,
+            ColumnData::Text { codes, dictionary } => {
+                check_len("column values", codes.len(), len)?;
+                let in_range = codes
+                    .iter()
+                    .zip(&valid)
+                    .all(|(&c, &ok)| !ok || (c as usize) < dictionary.len());
+                if !in_range {
+                    return Err(ComputeError::invalid("dictionary code out of range"));
+                }
+            }
+        }
+        let null_count = valid.iter().filter(|&&v| !v).count();
+        Ok(Column {
+            name,
+            data,
+            valid,
+            null_count,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn data(&self) -> &ColumnData {
+        &self.data
+    }
+
+    pub fn column_type(&self) -> ColumnType {
+        self.data.column_type()
+    }
+
+    pub fn len(&self) -> usize {
+        self.valid.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.valid.is_empty()
+    }
+
+    pub fn validity(&self) -> &[bool] {
+        &self.valid
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    /// Numeric view with nulls as NaN; booleans map to 0 and 1
+    pub fn to_f64(&self) -> Option<Vec<f64>> {
+        let values = match &self.data {
+            ColumnData::Null => vec![f64::NAN; self.len()],
+            ColumnData::Boolean(v) => v.iter().map(|&b| f64::from(u8::from(b))).collect(),
+            ColumnData::Integer(v) => v.iter().map(|&x| x as f64).collect(),
+            ColumnData::Float(v) => v.clone(),
+            ColumnData::Text { .. } => return None,
+        };
+        Some(
+            values
+                .into_iter()
+                .zip(&self.valid)
+                .map(|(x, &ok)| if ok { x } else { f64::NAN })
+                .collect(),
+        )
+    }
+
+    /// Value of row `i`
+    pub fn cell(&self, i: usize) -> Cell<'_> {
+        if !self.valid[i] {
+            return Cell::Null;
+        }
+        match &self.data {
+            ColumnData::Null => Cell::Null,
+            ColumnData::Boolean(v) => Cell::Boolean(v[i]),
+            ColumnData::Integer(v) => Cell::Integer(v[i]),
+            ColumnData::Float(v) => Cell::Float(v[i]),
+            ColumnData::Text { codes, dictionary } => Cell::Text(&dictionary[codes[i] as usize]),
+        }
+    }
+
+    /// Value of row `i` rendered as text, `None` when null
+    pub fn text(&self, i: usize) -> Option<String> {
+        if !self.valid[i] {
+            return None;
+        }
+        let mut out = String::new();
+        render_into(&self.data, i, None, &mut out);
+        Some(out)
+    }
+}
+
+/// Row `i` of `data` as text, floats with `decimals` fraction digits if set;
+/// the row must not be null
+fn render_into(data: &ColumnData, i: usize, decimals: Option<usize>, out: &mut String) {
+    use std::fmt::Write;
+    // Writing to a String cannot fail
+    let _ = match data {
+        ColumnData::Null => unreachable!("null columns have no valid rows"),
+        ColumnData::Boolean(v) => write!(out, "{}", v[i]),
+        ColumnData::Integer(v) => write!(out, "{}", v[i]),
+        ColumnData::Float(v) => match decimals {
+            Some(k) => write!(out, "{:.k$}", v[i]),
+            None => write!(out, "{}", v[i]),
+        },
+        ColumnData::Text { codes, dictionary } => {
+            out.push_str(&dictionary[codes[i] as usize]);
+            Ok(())
+        }
+    };
+}
+
+/// Accumulates one column, widening its type as values arrive
+///
+/// Integers widen to floats; any other mix of types becomes text. Values
+/// pushed with `push_field` keep their source text through that widening
+/// (`"02134"` stays `"02134"`); values pushed with `push` are rendered by
+/// `to_string`.
+///
+/// Float text is re-derived on widening with as many fraction digits as the
+/// column's first float field had, so `"12.50"`, `"3.10"` need no extra
+/// memory. Only texts that differ from that are kept, and at most
+/// `MAX_RAW_FIELDS` of them; past the cap, widened rows read back rendered.
+#[derive(Debug, Clone)]
+pub struct ColumnBuilder {
+    name: String,
+    data: ColumnData,
+    valid: Vec<bool>,
+    lookup: HashMap<String, u32>,
+    /// Source text of the non-text rows that cannot be re-derived from
+    /// their value, needed only if the column widens to text
+    raw: HashMap<usize, Box<str>>,
+    /// Fraction digits of the first float field (`"1.50"` → 2), `None` for
+    /// the shortest rendering
+    decimals: Option<usize>,
+    scratch: String,
+}
+
+impl ColumnBuilder {
+    pub fn new(name: String) -> ColumnBuilder {
+        ColumnBuilder {
+            name,
+            data: ColumnData::Null,
+            valid: Vec::new(),
+            lookup: HashMap::new(),
+            raw: HashMap::new(),
+            decimals: None,
+            scratch: String::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn len(&self) -> usize {
+        self.valid.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.valid.is_empty()
+    }
+
+    pub fn column_type(&self) -> ColumnType {
+        self.data.column_type()
+    }
+
+    pub fn push(&mut self, cell: Cell) -> Result<()> {
+        let row = self.len();
+        self.append(cell)?;
+        if self.decimals.is_some() && matches!(cell, Cell::Integer(_) | Cell::Float(_)) {
+            // Keep the `to_string` rendering rather than the fixed-point one
+            let text = match cell {
+                Cell::Integer(x) => x.to_string(),
+                Cell::Float(x) => x.to_string(),
+                _ => unreachable!(),
+            };
+            self.check_text(row, &text);
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, cell: Cell) -> Result<()> {
+        let target = self.column_type().join(cell.column_type());
+        if target != self.column_type() {
+            self.widen(target)?;
+        }
+        self.valid.push(cell != Cell::Null);
+        match (&mut self.data, cell) {
+            (ColumnData::Null, _) => {}
+            (ColumnData::Boolean(v), Cell::Boolean(b)) => v.push(b),
+            (ColumnData::Boolean(v), _) => v.push(false),
+            (ColumnData::Integer(v), Cell::Integer(x)) => v.push(x),
+            (ColumnData::Integer(v), _) => v.push(0),
+            (ColumnData::Float(v), Cell::Float(x)) => v.push(x),
+            (ColumnData::Float(v), Cell::Integer(x)) => v.push(x as f64),
+            (ColumnData::Float(v), _) => v.push(0.0),
+            (ColumnData::Text { .. }, cell) => {
+                let code = match cell {
+                    Cell::Null => NULL_CODE,
+                    Cell::Text(s) => self.intern(s)?,
+                    Cell::Boolean(b) => self.intern(&b.to_string())?,
+                    Cell::Integer(x) => self.intern(&x.to_string())?,
+                    Cell::Float(x) => self.intern(&x.to_string())?,
+                };
+                self.push_code(code);
+            }
+        }
+        Ok(())
+    }
+
+    /// Push `cell`, parsed from the text `field`. Should the column be or
+    /// become text, the cell reads back as `field` verbatim.
+    pub fn push_field(&mut self, cell: Cell, field: &str) -> Result<()> {
+        if cell == Cell::Null {
+            return self.push(cell);
+        }
+        if self.column_type().join(cell.column_type()) == ColumnType::Text {
+            return self.append(Cell::Text(field));
+        }
+        if let Cell::Float(x) = cell {
+            if self.column_type() != ColumnType::Float {
+                self.decimals = fraction_digits(x, field);
+            }
+        }
+        let row = self.len();
+        self.append(cell)?;
+        self.check_text(row, field);
+        Ok(())
+    }
+
+    /// Remember `text` for `row` if widening would not re-derive it
+    fn check_text(&mut self, row: usize, text: &str) {
+        if self.column_type() == ColumnType::Text || self.raw.contains_key(&row) {
+            return;
+        }
+        self.scratch.clear();
+        render_into(&self.data, row, self.decimals, &mut self.scratch);
+        if self.scratch != text && self.raw.len() < MAX_RAW_FIELDS {
+            self.raw.insert(row, text.into());
+        }
+    }
+
+    fn push_code(&mut self, code: u32) {
+        if let ColumnData::Text { codes, .. } = &mut self.data {
+            codes.push(code);
+        }
+    }
+
+    fn intern(&mut self, s: &str) -> Result<u32> {
+        if let Some(&code) = self.lookup.get(s) {
+            return Ok(code);
+        }
+        let ColumnData::Text { dictionary, .. } = &mut self.data else {
+            unreachable!("interning into a non-text column");
+        };
+        let code = u32::try_from(dictionary.len())
+            .ok()
+            .filter(|&c| c != NULL_CODE)
+            .ok_or(ComputeError::Overflow("dictionary size exceeds u32 codes"))?;
+        dictionary.push(s.to_owned());
+        self.lookup.insert(s.to_owned(), code);
+        Ok(code)
+    }
+
+    fn widen(&mut self, to: ColumnType) -> Result<()> {
+        let len = self.len();
+        let old = std::mem::replace(&mut self.data, ColumnData::Null);
+        self.data = match (old, to) {
+            (ColumnData::Null, ColumnType::Boolean) => ColumnData::Boolean(vec![false; len]),
+            (ColumnData::Null, ColumnType::Integer) => ColumnData::Integer(vec![0; len]),
+            (ColumnData::Null, ColumnType::Float) => ColumnData::Float(vec![0.0; len]),
+            (ColumnData::Integer(v), ColumnType::Float) => {
+                // Integers above 2^53, or any under fixed-point decimals,
+                // render differently as floats; their own rendering is still
+                // the source text
+                self.data = ColumnData::Float(v.iter().map(|&x| x as f64).collect());
+                for (i, &x) in v.iter().enumerate() {
+                    if self.valid[i] {
+                        self.check_text(i, &x.to_string());
+                    }
+                }
+                return Ok(());
+            }
+            (old, ColumnType::Text) => {
+                self.data = ColumnData::Text {
+                    codes: Vec::with_capacity(len),
+                    dictionary: Vec::new(),
+                };
+                // Text columns never widen again, so the source texts go now
+                let mut raw = std::mem::take(&mut self.raw);
+                let mut text = String::new();
+                for i in 0..len {
+                    let code = if !self.valid[i] {
+                        NULL_CODE
+                    } else if let Some(text) = raw.remove(&i) {
+                        self.intern(&text)?
+                    } else {
+                        text.clear();
+                        render_into(&old, i, self.decimals, &mut text);
+                        self.intern(&text)?
+                    };
+                    self.push_code(code);
+                }
+                return Ok(());
+            }
+            (old, to) => unreachable!("cannot widen {:?} to {to:?}", old.column_type()),
+        };
+        Ok(())
+    }
+
+    pub fn finish(self) -> Column {
+        let null_count = self.valid.iter().filter(|&&v| !v).count();
+        Column {
+            name: self.name,
+            data: self.data,
+            valid: self.valid,
+            null_count,
+        }
+    }
+}
+
+/// Fraction digits of a plain decimal `field` that `{:.k$}` reproduces from `x`
+fn fraction_digits(x: f64, field: &str) -> Option<usize> {
+    let (int, frac) = field.split_once('.')?;
+    let int = int.strip_prefix(['+', '-']).unwrap_or(int);
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(int) || !digits(frac) || frac.is_empty() {
+        return None;
+    }
+    let k = frac.len();
+    (format!("{x:.k$}") == field).then_some(k)
+}
+
+#[derive(Serialize)]
+struct ColumnSchema<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    column_type: ColumnType,
+    #[serde(rename = "nullCount")]
+    null_count: usize,
+}
+
+/// Columns of equal length
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    rows: usize,
+    columns: Vec<Column>,
+}
+
+impl Table {
+    pub fn from_columns(columns: Vec<Column>) -> Result<Table> {
+        let rows = columns.first().map_or(0, Column::len);
+        for column in &columns {
+            check_len("table column", column.len(), rows)?;
+        }
+        Ok(Table { rows, columns })
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn column(&self, i: usize) -> Result<&Column> {
+        self.columns.get(i).ok_or_else(|| {
+            ComputeError::invalid(format!(
+                "column {i} out of range for a table with {} columns",
+                self.columns.len()
+            ))
+        })
+    }
+
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.name == name)
+    }
+}
+
+#[wasm_bindgen]
+impl Table {
+    #[wasm_bindgen(getter, js_name = rowCount)]
+    pub fn js_row_count(&self) -> usize {
+        self.rows
+    }
+
+    #[wasm_bindgen(getter, js_name = columnCount)]
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    #[wasm_bindgen(js_name = columnNames)]
+    pub fn column_names(&self) -> Vec<String> {
+        self.columns.iter().map(|c| c.name.clone()).collect()
+    }
+
+    /// Index of the column called `name`, or `undefined`
+    #[wasm_bindgen(js_name = columnIndex)]
+    pub fn js_column_index(&self, name: &str) -> Option<usize> {
+        self.column_index(name)
+    }
+
+    #[wasm_bindgen(js_name = columnType)]
+    pub fn js_column_type(&self, i: usize) -> Result<ColumnType, JsError> {
+        Ok(self.column(i)?.column_type())
+    }
+
+    /// `[{ name, type, nullCount }]` for every column
+    pub fn schema(&self) -> Result<JsValue, JsError> {
+        let schema: Vec<ColumnSchema> = self
+            .columns
+            .iter()
+            .map(|c| ColumnSchema {
+                name: &c.name,
+                column_type: c.column_type(),
+                null_count: c.null_count,
+            })
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&schema)?)
+    }
+
+    /// 1 for present values, 0 for nulls
+    pub fn validity(&self, i: usize) -> Result<Vec<u8>, JsError> {
+        Ok(self.column(i)?.valid.iter().map(|&v| u8::from(v)).collect())
+    }
+
+    /// Numeric or boolean column as a `Float64Array`, nulls as NaN
+    pub fn numbers(&self, i: usize) -> Result<Vec<f64>, JsError> {
+        let column = self.column(i)?;
+        column.to_f64().ok_or_else(|| {
+            ComputeError::invalid(format!("column {:?} is text, not numeric", column.name)).into()
+        })
+    }
+
+    /// Integer column as a `BigInt64Array` (exact beyond 2^53), nulls as 0
+    pub fn integers(&self, i: usize) -> Result<Vec<i64>, JsError> {
+        match &self.column(i)?.data {
+            ColumnData::Integer(v) => Ok(v.clone()),
+            _ => Err(ComputeError::invalid(format!("column {i} is not an integer column")).into()),
+        }
+    }
+
+    /// Boolean column as a `Uint8Array` of 0/1, nulls as 0
+    pub fn booleans(&self, i: usize) -> Result<Vec<u8>, JsError> {
+        match &self.column(i)?.data {
+            ColumnData::Boolean(v) => Ok(v.iter().map(|&b| u8::from(b)).collect()),
+            _ => Err(ComputeError::invalid(format!("column {i} is not a boolean column")).into()),
+        }
+    }
+
+    /// Dictionary codes of a text column; nulls are `0xFFFFFFFF`
+    pub fn codes(&self, i: usize) -> Result<Vec<u32>, JsError> {
+        match &self.column(i)?.data {
+            ColumnData::Text { codes, .. } => Ok(codes.clone()),
+            _ => Err(ComputeError::invalid(format!("column {i} is not a text column")).into()),
+        }
+    }
+
+    /// Distinct strings of a text column, indexed by code
+    pub fn dictionary(&self, i: usize) -> Result<Vec<String>, JsError> {
+        match &self.column(i)?.data {
+            ColumnData::Text { dictionary, .. } => Ok(dictionary.clone()),
+            _ => Err(ComputeError::invalid(format!("column {i} is not a text column")).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_widens() {
+        let mut b = ColumnBuilder::new("x".into());
+        b.push(Cell::Null).unwrap();
+        b.push(Cell::Integer(2)).unwrap();
+        assert_eq!(b.column_type(), ColumnType::Integer);
+        b.push(Cell::Float(0.5)).unwrap();
+        let c = b.clone().finish();
+        assert_eq!(c.column_type(), ColumnType::Float);
+        let v = c.to_f64().unwrap();
+        assert!(v[0].is_nan());
+        assert_eq!(&v[1..], &[2.0, 0.5]);
+
+        b.push(Cell::Text("two")).unwrap();
+        b.push(Cell::Float(0.5)).unwrap();
+        let c = b.finish();
+        assert_eq!(c.null_count(), 1);
+        let ColumnData::Text { codes, dictionary } = c.data() else {
+            panic!("expected text");
+        };
+        assert_eq!(codes, &[NULL_CODE, 0, 1, 2, 1]);
+        assert_eq!(dictionary, &["2", "0.5", "two"]);
+    }
+
+    #[test]
+    fn test_widening_keeps_field_text() {
+        let fields = [
+            (Cell::Integer(2134), "02134"),
+            (Cell::Integer(12), "12"),
+            // Renders as itself, but not once widened to a float
+            (
+                Cell::Integer(1_152_921_504_606_846_977),
+                "1152921504606846977",
+            ),
+            (Cell::Float(1.5), "1.50"),
+            (Cell::Float(1000.0), "1e3"),
+        ];
+        let mut b = ColumnBuilder::new("zip".into());
+        for (cell, field) in fields {
+            b.push_field(cell, field).unwrap();
+        }
+        // Still numeric until now
+        assert_eq!(b.column_type(), ColumnType::Float);
+        b.push_field(Cell::Text("n/a"), "n/a").unwrap();
+        // Cells after the widen keep their text too
+        b.push_field(Cell::Integer(7), "007").unwrap();
+        b.push_field(Cell::Boolean(true), "TRUE").unwrap();
+
+        let c = b.finish();
+        let texts: Vec<String> = (0..c.len()).filter_map(|i| c.text(i)).collect();
+        assert_eq!(
+            texts,
+            [
+                "02134",
+                "12",
+                "1152921504606846977",
+                "1.50",
+                "1e3",
+                "n/a",
+                "007",
+                "TRUE"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fixed_point_floats_keep_no_text() {
+        let mut b = ColumnBuilder::new("price".into());
+        for i in 0..1000 {
+            let field = format!("{i}.50");
+            b.push_field(Cell::Float(field.parse().unwrap()), &field)
+                .unwrap();
+        }
+        assert!(b.raw.is_empty());
+        // Only the odd one out is remembered, and the texts are re-derived
+        b.push_field(Cell::Float(7.25), "7.250").unwrap();
+        b.push_field(Cell::Integer(3), "3").unwrap();
+        assert_eq!(b.raw.len(), 2);
+        b.push_field(Cell::Text("n/a"), "n/a").unwrap();
+        assert!(b.raw.is_empty());
+        let c = b.finish();
+        assert_eq!(c.text(0).as_deref(), Some("0.50"));
+        assert_eq!(c.text(999).as_deref(), Some("999.50"));
+        let tail: Vec<String> = (1000..1003).filter_map(|i| c.text(i)).collect();
+        assert_eq!(tail, ["7.250", "3", "n/a"]);
+
+        // The cap bounds what is kept; rows past it read back rendered
+        let mut b = ColumnBuilder::new("zip".into());
+        for i in 0..MAX_RAW_FIELDS + 10 {
+            b.push_field(Cell::Integer(i as i64), &format!("0{i}"))
+                .unwrap();
+        }
+        assert_eq!(b.raw.len(), MAX_RAW_FIELDS);
+        b.push_field(Cell::Text("x"), "x").unwrap();
+        let c = b.finish();
+        assert_eq!(c.text(1).as_deref(), Some("01"));
+        assert_eq!(
+            c.text(MAX_RAW_FIELDS).as_deref(),
+            Some(&*MAX_RAW_FIELDS.to_string())
+        );
+    }
+
+    #[test]
+    fn test_table_lengths() {
+        let a = Column::new("a".into(), ColumnData::Integer(vec![1, 2]), vec![true; 2]).unwrap();
+        let b = Column::new("b".into(), ColumnData::Null, vec![false; 3]).unwrap();
+        assert!(Table::from_columns(vec![a.clone(), b]).is_err());
+        assert!(Column::new("c".into(), ColumnData::Float(vec![1.0]), vec![true; 2]).is_err());
+
+        let t = Table::from_columns(vec![a]).unwrap();
+        assert_eq!(t.row_count(), 2);
+        assert_eq!(t.column_index("a"), Some(0));
+        assert!(t.column(1).is_err());
+    }
+}
@@ -0,0 +1,471 @@
+//! Geometric transforms of RGBA buffers.
+//!
+//! Quarter turns, flips and crops move whole pixels. Arbitrary rotations and
+//! affine or perspective warps map every output pixel centre back into the
+//! source and interpolate there with a [`ResizeFilter`] kernel, in
+//! premultiplied alpha; whatever maps outside the source is transparent.
+//! Every function returns a new [`RgbaImage`] carrying its dimensions.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{check_len, checked_area, try_filled, ComputeError, Result};
+use crate::image::{check_image, premultiplied, store_premultiplied, RgbaImage};
+use crate::resize::ResizeFilter;
+
+type Mat3 = [[f64; 3]; 3];
+
+/// Byte length of a `width × height` RGBA buffer
+fn rgba_bytes(width: u32, height: u32) -> Result<usize> {
+    checked_area(width as usize, height as usize)?
+        .checked_mul(4)
+        .ok_or(ComputeError::Overflow("image dimensions"))
+}
+
+/// Build a `dst_width × dst_height` image taking pixel `(x, y)` from `src(x, y)`
+fn remap(
+    data: &[u8],
+    width: usize,
+    dst_width: u32,
+    dst_height: u32,
+    src: impl Fn(usize, usize) -> (usize, usize),
+) -> Result<RgbaImage> {
+    let mut out = try_filled(rgba_bytes(dst_width, dst_height)?, 0u8)?;
+    let dw = dst_width as usize;
+    for (i, px) in out.chunks_exact_mut(4).enumerate() {
+        let (sx, sy) = src(i % dw, i / dw);
+        let j = (sy * width + sx) * 4;
+        px.copy_from_slice(&data[j..j + 4]);
+    }
+    RgbaImage::new(out, dst_width, dst_height)
+}
+
+/// Rotate clockwise by `turns` quarter turns (negative is counter-clockwise)
+pub fn rotate_quarter_turns_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    turns: i32,
+) -> Result<RgbaImage> {
+    check_image(data, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+    match turns.rem_euclid(4) {
+        0 => RgbaImage::new(data.to_vec(), width, height),
+        1 => remap(data, w, height, width, |x, y| (y, h - 1 - x)),
+        2 => remap(data, w, width, height, |x, y| (w - 1 - x, h - 1 - y)),
+        _ => remap(data, w, height, width, |x, y| (w - 1 - y, x)),
+    }
+}
+
+/// Mirror left-right and/or top-bottom
+pub fn flip_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    horizontal: bool,
+    vertical: bool,
+) -> Result<RgbaImage> {
+    check_image(data, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+    remap(data, w, width, height, |x, y| {
+        (
+            if horizontal { w - 1 - x } else { x },
+            if vertical { h - 1 - y } else { y },
+        )
+    })
+}
+
+/// The `crop_width × crop_height` rectangle whose top-left pixel is `(x, y)`
+pub fn crop_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    (x, y): (u32, u32),
+    crop_width: u32,
+    crop_height: u32,
+) -> Result<RgbaImage> {
+    check_image(data, width, height)?;
+    let fits = |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|e| e <= max);
+    if !fits(x, crop_width, width) || !fits(y, crop_height, height) {
+        return Err(ComputeError::invalid(format!(
+            "crop {crop_width}×{crop_height} at ({x}, {y}) exceeds the {width}×{height} image"
+        )));
+    }
+    let (x, y) = (x as usize, y as usize);
+    remap(data, width as usize, crop_width, crop_height, |cx, cy| {
+        (x + cx, y + cy)
+    })
+}
+
+fn invert(m: &Mat3) -> Result<Mat3> {
+    let cofactor = |r: usize, c: usize| {
+        let (r0, r1) = ((r + 1) % 3, (r + 2) % 3);
+        let (c0, c1) = ((c + 1) % 3, (c + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let det: f64 = (0..3).map(|c| m[0][c] * cofactor(0, c)).sum();
+    if det == 0.0 || !det.is_finite() {
+        return Err(ComputeError::SingularMatrix);
+    }
+    // Inverse is the transposed cofactor matrix over the determinant
+    Ok(std::array::from_fn(|r| {
+        std::array::from_fn(|c| cofactor(c, r) / det)
+    }))
+}
+
+/// Interpolate premultiplied `src` at continuous position `(u, v)`
+/// (pixel `(i, j)` covers `i..i + 1 × j..j + 1`)
+fn sample(src: &[f32], w: usize, h: usize, u: f32, v: f32, filter: ResizeFilter) -> [f32; 4] {
+    const MAX_TAPS: usize = 8;
+    let mut acc = [0.0f32; 4];
+    if filter == ResizeFilter::Nearest {
+        let (x, y) = (u.floor(), v.floor());
+        if x >= 0.0 && y >= 0.0 && (x as usize) < w && (y as usize) < h {
+            let i = (y as usize * w + x as usize) * 4;
+            acc.copy_from_slice(&src[i..i + 4]);
+        }
+        return acc;
+    }
+    let support = filter.support();
+    // Taps and normalized weights along one axis; outside taps keep their
+    // weight (as transparent black) so edges fade instead of smearing
+    let taps = |t: f32, n: usize| {
+        let t = t - 0.5;
+        let first = (t - support).ceil() as isize;
+        let mut idx = [None; MAX_TAPS];
+        let mut wts = [0.0f32; MAX_TAPS];
+        let mut total = 0.0;
+        for k in 0..MAX_TAPS {
+            let j = first + k as isize;
+            if j as f32 > t + support {
+                break;
+            }
+            wts[k] = filter.weight(t - j as f32);
+            total += wts[k];
+            idx[k] = (0..n as isize).contains(&j).then_some(j as usize);
+        }
+        if total != 0.0 {
+            wts.iter_mut().for_each(|x| *x /= total);
+        }
+        (idx, wts)
+    };
+    let (xs, wx) = taps(u, w);
+    let (ys, wy) = taps(v, h);
+    for (y, &wy) in ys.iter().zip(&wy) {
+        let Some(y) = y else { continue };
+        for (x, &wx) in xs.iter().zip(&wx) {
+            let Some(x) = x else { continue };
+            let i = (y * w + x) * 4;
+            let k = wx * wy;
+            for c in 0..4 {
+                acc[c] += k * src[i + c];
+            }
+        }
+    }
+    acc
+}
+
+/// Resample through the projective map `forward` (source → destination)
+fn warp(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    forward: Mat3,
+    dst_width: u32,
+    dst_height: u32,
+    filter: ResizeFilter,
+) -> Result<RgbaImage> {
+    check_image(data, width, height)?;
+    let (w, h) = (width as usize, height as usize);
+    let dw = dst_width as usize;
+    let bytes = rgba_bytes(dst_width, dst_height)?;
+    // Scale the matrix so the source centre has a positive w; destination
+    // points whose preimage has w <= 0 lie beyond the horizon
+    let centre = forward[2][0] * w as f64 / 2.0 + forward[2][1] * h as f64 / 2.0 + forward[2][2];
+    let sign = if centre < 0.0 { -1.0 } else { 1.0 };
+    let inverse = invert(&forward.map(|row| row.map(|x| x * sign)))?;
+
+    let src = premultiplied(data);
+    let mut out = try_filled(bytes, 0.0f32)?;
+    for (i, px) in out.chunks_exact_mut(4).enumerate() {
+        let (x, y) = ((i % dw) as f64 + 0.5, (i / dw) as f64 + 0.5);
+        let [su, sv, sz] = inverse.map(|r| r[0] * x + r[1] * y + r[2]);
+        if sz > 0.0 {
+            let (u, v) = ((su / sz) as f32, (sv / sz) as f32);
+            px.copy_from_slice(&sample(&src, w, h, u, v, filter));
+        }
+    }
+    let mut pixels = try_filled(bytes, 0u8)?;
+    store_premultiplied(&out, &mut pixels);
+    RgbaImage::new(pixels, dst_width, dst_height)
+}
+
+/// Affine warp; `matrix` is `[a, b, c, d, e, f]` as in canvas `setTransform`,
+/// mapping source `(x, y)` to `(a x + c y + e, b x + d y + f)`
+pub fn affine_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    matrix: &[f32],
+    dst_width: u32,
+    dst_height: u32,
+    filter: ResizeFilter,
+) -> Result<RgbaImage> {
+    check_len("affine matrix", matrix.len(), 6)?;
+    let m = matrix.iter().map(|&x| f64::from(x)).collect::<Vec<_>>();
+    let forward = [[m[0], m[2], m[4]], [m[1], m[3], m[5]], [0.0, 0.0, 1.0]];
+    warp(data, width, height, forward, dst_width, dst_height, filter)
+}
+
+/// Perspective warp by a row-major 3×3 homography from source to destination
+pub fn perspective_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    matrix: &[f32],
+    dst_width: u32,
+    dst_height: u32,
+    filter: ResizeFilter,
+) -> Result<RgbaImage> {
+    check_len("homography", matrix.len(), 9)?;
+    let forward = std::array::from_fn(|r| std::array::from_fn(|c| f64::from(matrix[r * 3 + c])));
+    warp(data, width, height, forward, dst_width, dst_height, filter)
+}
+
+/// Rotate clockwise by `degrees` about the image centre
+///
+/// With `expand` the output grows to hold the whole rotated image, otherwise
+/// it keeps the input size and the corners are cut off. Multiples of 90° that
+/// fit the output exactly take the lossless quarter-turn path.
+pub fn rotate_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    degrees: f32,
+    filter: ResizeFilter,
+    expand: bool,
+) -> Result<RgbaImage> {
+    if !degrees.is_finite() {
+        return Err(ComputeError::invalid("rotation angle must be finite"));
+    }
+    if degrees.rem_euclid(90.0) == 0.0 {
+        let turns = (degrees.rem_euclid(360.0) / 90.0) as i32;
+        if expand || turns % 2 == 0 || width == height {
+            return rotate_quarter_turns_rgba(data, width, height, turns);
+        }
+    }
+    let (sin, cos) = f64::from(degrees).to_radians().sin_cos();
+    let (w, h) = (f64::from(width), f64::from(height));
+    let (dw, dh) = if expand {
+        // Tolerance keeps e.g. 45° on an integer grid from gaining a pixel
+        let fit = |extent: f64| (extent - 1e-6).ceil().max(0.0) as u32;
+        (
+            fit(w * cos.abs() + h * sin.abs()),
+            fit(w * sin.abs() + h * cos.abs()),
+        )
+    } else {
+        (width, height)
+    };
+    let (cx, cy) = (w / 2.0, h / 2.0);
+    let (ox, oy) = (f64::from(dw) / 2.0, f64::from(dh) / 2.0);
+    let forward = [
+        [cos, -sin, ox - cos * cx + sin * cy],
+        [sin, cos, oy - sin * cx - cos * cy],
+        [0.0, 0.0, 1.0],
+    ];
+    warp(data, width, height, forward, dw, dh, filter)
+}
+
+/// Rotate `ImageData` pixels by `turns × 90°` clockwise (lossless)
+#[wasm_bindgen]
+pub fn rotate_quarter_turns(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    turns: i32,
+) -> Result<RgbaImage, JsError> {
+    Ok(rotate_quarter_turns_rgba(data, width, height, turns)?)
+}
+
+/// Rotate by any angle in degrees (clockwise), interpolating with `filter`
+#[wasm_bindgen]
+pub fn rotate(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    degrees: f32,
+    filter: ResizeFilter,
+    expand: bool,
+) -> Result<RgbaImage, JsError> {
+    Ok(rotate_rgba(data, width, height, degrees, filter, expand)?)
+}
+
+#[wasm_bindgen]
+pub fn flip(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    horizontal: bool,
+    vertical: bool,
+) -> Result<RgbaImage, JsError> {
+    Ok(flip_rgba(data, width, height, horizontal, vertical)?)
+}
+
+#[wasm_bindgen]
+pub fn crop(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+    crop_width: u32,
+    crop_height: u32,
+) -> Result<RgbaImage, JsError> {
+    Ok(crop_rgba(
+        data,
+        width,
+        height,
+        (x, y),
+        crop_width,
+        crop_height,
+    )?)
+}
+
+/// Affine warp into a `dstWidth × dstHeight` image (`matrix` as in `setTransform`)
+#[wasm_bindgen]
+pub fn warp_affine(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    matrix: &[f32],
+    dst_width: u32,
+    dst_height: u32,
+    filter: ResizeFilter,
+) -> Result<RgbaImage, JsError> {
+    Ok(affine_rgba(
+        data, width, height, matrix, dst_width, dst_height, filter,
+    )?)
+}
+
+/// Perspective warp by a row-major 3×3 source → destination homography
+#[wasm_bindgen]
+pub fn warp_perspective(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    matrix: &[f32],
+    dst_width: u32,
+    dst_height: u32,
+    filter: ResizeFilter,
+) -> Result<RgbaImage, JsError> {
+    Ok(perspective_rgba(
+        data, width, height, matrix, dst_width, dst_height, filter,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3×2 opaque image whose red channel is the pixel index
+    fn sample_image() -> Vec<u8> {
+        (0..6u8).flat_map(|i| [i, 10, 20, 255]).collect()
+    }
+
+    fn reds(image: &RgbaImage) -> Vec<u8> {
+        image.pixels().chunks(4).map(|p| p[0]).collect()
+    }
+
+    #[test]
+    fn test_lossless_transforms() {
+        let data = sample_image();
+        let cw = rotate_quarter_turns_rgba(&data, 3, 2, 1).unwrap();
+        assert_eq!((cw.width(), cw.height()), (2, 3));
+        // 0 1 2      3 0
+        // 3 4 5  ->  4 1
+        //            5 2
+        assert_eq!(reds(&cw), [3, 0, 4, 1, 5, 2]);
+        let ccw = rotate_quarter_turns_rgba(&data, 3, 2, -1).unwrap();
+        assert_eq!(reds(&ccw), [2, 5, 1, 4, 0, 3]);
+        assert_eq!(ccw, rotate_quarter_turns_rgba(&data, 3, 2, 3).unwrap());
+
+        let half = rotate_quarter_turns_rgba(&data, 3, 2, 2).unwrap();
+        assert_eq!(half, flip_rgba(&data, 3, 2, true, true).unwrap());
+        assert_eq!(
+            reds(&flip_rgba(&data, 3, 2, true, false).unwrap()),
+            [2, 1, 0, 5, 4, 3]
+        );
+        assert_eq!(
+            rotate_rgba(&data, 3, 2, -270.0, ResizeFilter::Lanczos3, true).unwrap(),
+            cw
+        );
+
+        let c = crop_rgba(&data, 3, 2, (1, 0), 2, 2).unwrap();
+        assert_eq!((c.width(), c.height(), reds(&c)), (2, 2, vec![1, 2, 4, 5]));
+        assert!(crop_rgba(&data, 3, 2, (2, 0), 2, 1).is_err());
+        assert!(crop_rgba(&data, 3, 2, (u32::MAX, 0), 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_warps() {
+        let data = sample_image();
+        // Quarter turn expressed as an affine map lands exactly on pixel centres
+        let cw = rotate_quarter_turns_rgba(&data, 3, 2, 1).unwrap();
+        for filter in [
+            ResizeFilter::Nearest,
+            ResizeFilter::Bilinear,
+            ResizeFilter::Bicubic,
+        ] {
+            let m = [0.0, 1.0, -1.0, 0.0, 2.0, 0.0];
+            assert_eq!(affine_rgba(&data, 3, 2, &m, 2, 3, filter).unwrap(), cw);
+        }
+        let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        let same = perspective_rgba(&data, 3, 2, &identity, 3, 2, ResizeFilter::Lanczos3).unwrap();
+        assert_eq!(same.into_pixels(), data);
+        // Doubling the homography's scale changes nothing
+        let doubled = identity.map(|x| -2.0 * x);
+        let same = perspective_rgba(&data, 3, 2, &doubled, 3, 2, ResizeFilter::Bilinear).unwrap();
+        assert_eq!(same.into_pixels(), data);
+
+        assert!(matches!(
+            affine_rgba(&data, 3, 2, &[0.0; 6], 3, 2, ResizeFilter::Bilinear),
+            Err(ComputeError::SingularMatrix)
+        ));
+        assert!(perspective_rgba(&data, 3, 2, &[1.0; 6], 3, 2, ResizeFilter::Bilinear).is_err());
+        // Oversized outputs are errors, not capacity overflows or aborts
+        let m = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        for (dw, dh) in [(u32::MAX, u32::MAX), (u32::MAX, 1 << 30)] {
+            assert!(affine_rgba(&data, 3, 2, &m, dw, dh, ResizeFilter::Nearest).is_err());
+        }
+    }
+
+    #[test]
+    fn test_rotate_any_angle() {
+        let data = [90, 160, 30, 255].repeat(10 * 10);
+        let r = rotate_rgba(&data, 10, 10, 45.0, ResizeFilter::Bilinear, true).unwrap();
+        assert_eq!((r.width(), r.height()), (15, 15));
+        let px = |x: usize, y: usize| &r.pixels()[(y * 15 + x) * 4..][..4];
+        // Corners fall outside the source; the middle keeps its colour
+        assert_eq!(px(0, 0)[3], 0);
+        assert_eq!(px(14, 14)[3], 0);
+        assert_eq!(px(7, 7), [90, 160, 30, 255]);
+        // Partially covered edge pixels keep the colour, only alpha fades
+        let edges: Vec<&[u8]> = r
+            .pixels()
+            .chunks(4)
+            .filter(|p| (32..255).contains(&p[3]))
+            .collect();
+        assert!(!edges.is_empty());
+        for p in edges {
+            let colour_error = p
+                .iter()
+                .zip([90, 160, 30])
+                .map(|(a, b)| a.abs_diff(b))
+                .max();
+            assert!(colour_error <= Some(2), "{p:?}");
+        }
+
+        let kept = rotate_rgba(&data, 10, 10, 30.0, ResizeFilter::Bicubic, false).unwrap();
+        assert_eq!((kept.width(), kept.height()), (10, 10));
+        assert!(rotate_rgba(&data, 10, 10, f32::NAN, ResizeFilter::Bilinear, true).is_err());
+    }
+}
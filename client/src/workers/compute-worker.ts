@@ -88,20 +88,17 @@ async function wasmCalculation(fn: string, opts: any) {
       return null;
     }
 
-    try {
-      // Check if the requested function exists in WASM module
-      if (typeof wasmModule[fn] !== 'function') {
-        console.warn(`WASM function '${fn}' not found`);
-        return null;
-      }
-
-      // Call the WASM function with provided arguments
-      const result = wasmModule[fn](...(Array.isArray(opts) ? opts : [opts]));
-      return result;
-    } catch (error) {
-      console.error('WASM computation failed:', error);
+    // Check if the requested function exists in WASM module
+    if (typeof wasmModule[fn] !== 'function') {
+      console.warn(`WASM function '${fn}' not found`);
       return null;
     }
+
+    // Call the WASM function with provided arguments.
+    // Exports throw a regular Error (dimension mismatch, overflow, ...) on bad
+    // input; let it propagate so the caller sees the message instead of
+    // silently falling back to JavaScript.
+    return wasmModule[fn](...(Array.isArray(opts) ? opts : [opts]));
   });
 }
 
@@ -167,10 +164,17 @@ function jsCalculatePrimes(max: number): number[] {
 
 /**
  * Sort large datasets
+ * Hands the key column to WASM `SortKeys` when available and reorders rows
+ * by the returned indices; falls back to Array.prototype.sort
  */
 function performSort(data: any[], params?: any): any[] {
   const { key, order = 'asc' } = params || {};
 
+  const indices = wasmArgsort(data, key, order);
+  if (indices) {
+    return Array.from(indices, (i: number) => data[i]);
+  }
+
   const sorted = [...data].sort((a, b) => {
     const valA = key ? a[key] : a;
     const valB = key ? b[key] : b;
@@ -183,6 +187,104 @@ function performSort(data: any[], params?: any): any[] {
   return sorted;
 }
 
+function wasmArgsort(data: any[], key: string | undefined, order: string): Uint32Array | null {
+  if (!wasmModule?.SortKeys || data.length === 0) return null;
+
+  const column = data.map(item => (key ? item[key] : item));
+  const direction = order === 'asc'
+    ? wasmModule.SortOrder.Ascending
+    : wasmModule.SortOrder.Descending;
+
+  const keys = new wasmModule.SortKeys();
+  try {
+    if (column.every(v => typeof v === 'number')) {
+      keys.addNumeric(Float64Array.from(column), direction, wasmModule.NanPlacement.Last);
+    } else if (column.every(v => typeof v === 'string')) {
+      keys.addStrings(column, direction);
+    } else {
+      // Mixed types keep JavaScript's comparison semantics
+      return null;
+    }
+    return keys.argsort();
+  } finally {
+    keys.free();
+  }
+}
+
+interface CachedSearchIndex {
+  fingerprint: string;
+  index: any;
+}
+
+// Search indexes kept between calls, keyed by params.indexKey
+const searchIndexes = new Map<string, CachedSearchIndex>();
+
+/**
+ * Identity of the indexed text: row count, fields and an FNV-1a hash of
+ * every value. Data arrives as a fresh copy on every message, so the
+ * content is the only identity a cached index can be checked against.
+ */
+function searchFingerprint(columns: string[][], fields: string[]): string {
+  let hash = 0x811c9dc5;
+  const mix = (code: number) => {
+    hash = Math.imul(hash ^ code, 0x01000193);
+  };
+  for (const column of columns) {
+    for (const value of column) {
+      for (let i = 0; i < value.length; i++) mix(value.charCodeAt(i));
+      // Value separator, outside the UTF-16 code unit range
+      mix(0x10000);
+    }
+  }
+  return `${columns[0]?.length ?? 0}:${fields.join('\u0000')}:${(hash >>> 0).toString(16)}`;
+}
+
+/**
+ * Ranked search through the WASM BM25 index (params.ranked).
+ * Matches whole tokens, or token prefixes unless params.prefix is false.
+ * Pass params.indexKey to reuse the index for later queries on the same data;
+ * an index whose data or fields changed is freed and rebuilt.
+ */
+function wasmSearch(data: any[], query: string, fields: string[], params: any): any[] | null {
+  if (!wasmModule?.SearchIndexBuilder || !Array.isArray(fields) || fields.length === 0) {
+    return null;
+  }
+  const { indexKey, limit = data.length, prefix = true, allTerms = false } = params;
+
+  const columns = fields.map(field =>
+    data.map(item => (item[field] == null ? '' : String(item[field])))
+  );
+  const fingerprint = indexKey ? searchFingerprint(columns, fields) : '';
+  const cached = indexKey ? searchIndexes.get(indexKey) : undefined;
+  let index = cached?.fingerprint === fingerprint ? cached.index : undefined;
+  if (cached && !index) {
+    // Stale: the dataset behind this key changed
+    cached.index.free();
+    searchIndexes.delete(indexKey);
+  }
+  if (!index) {
+    const builder = new wasmModule.SearchIndexBuilder();
+    try {
+      columns.forEach(column => builder.addField(column, 1));
+    } catch (error) {
+      builder.free();
+      throw error;
+    }
+    // build() consumes the builder
+    index = builder.build();
+    if (indexKey) searchIndexes.set(indexKey, { fingerprint, index });
+  }
+
+  try {
+    const hits = index.search(query, limit, prefix, allTerms);
+    const indices: Uint32Array = hits.indices;
+    hits.free();
+    return Array.from(indices, i => data[i]);
+  } finally {
+    if (!indexKey) index.free();
+  }
+}
+
 /**
  * Search in large datasets
  */
@@ -191,6 +293,11 @@ function performSearch(data: any[], params?: any): any[] {
 
   if (!query) return data;
 
+  if (params.ranked) {
+    const ranked = wasmSearch(data, query, fields, params);
+    if (ranked) return ranked;
+  }
+
   const lowerQuery = query.toLowerCase();
 
   return data.filter(item => {
@@ -237,14 +344,119 @@ function groupBy(data: any[], key: string): Record<string, any[]> {
   }, {} as Record<string, any[]>);
 }
 
-function pivot(data: any[], _params: any): any {
-  // Simple pivot implementation
-  return data;
+// Worker operation names → WASM `Aggregation` variants
+const WASM_AGGREGATIONS: Record<string, string> = {
+  sum: 'Sum',
+  avg: 'Mean',
+  count: 'Count',
+  min: 'Min',
+  max: 'Max',
+  median: 'Median',
+  stddev: 'StdDev',
+  distinct: 'DistinctCount'
+};
+
+function wasmGrouping(data: any[], key: string): any {
+  const keys = new wasmModule.GroupKeys();
+  try {
+    // Stringified like the object keys of the JavaScript groupBy
+    keys.addStrings(data.map(item => String(item[key])));
+  } catch (error) {
+    keys.free();
+    throw error;
+  }
+  // group() consumes the keys object
+  return keys.group();
+}
+
+function numericColumn(data: any[], field: string): Float64Array {
+  return Float64Array.from(data, item => Number(item[field]));
+}
+
+function wasmAggregation(operation: string): any {
+  const variant = WASM_AGGREGATIONS[operation];
+  return variant === undefined ? undefined : wasmModule.Aggregation[variant];
+}
+
+/**
+ * Pivot table: one output row per `rows` key, one property per `columns` key,
+ * holding the aggregation of `values` ('sum' by default).
+ * Empty cells are 0 for sum/count and null otherwise.
+ */
+function pivot(data: any[], params: any): any {
+  const { rows, columns, values, aggregation = 'sum' } = params || {};
+  if (!rows || !columns || !values) {
+    throw new Error('pivot requires rows, columns and values');
+  }
+
+  if (wasmModule?.GroupKeys && wasmAggregation(aggregation) !== undefined) {
+    let rowGroups: any;
+    let colGroups: any;
+    try {
+      rowGroups = wasmGrouping(data, rows);
+      colGroups = wasmGrouping(data, columns);
+      // count keeps its JavaScript meaning: rows in the cell, NaN or not
+      const table = aggregation === 'count'
+        ? rowGroups.pivotSizes(colGroups)
+        : rowGroups.pivot(colGroups, numericColumn(data, values), wasmAggregation(aggregation));
+      const cells: Float64Array = table.toArray();
+      const width: number = table.cols;
+      table.free();
+
+      const colKeys: string[] = colGroups.stringKey(0);
+      return rowGroups.stringKey(0).map((rowKey: string, r: number) => {
+        const out: any = { [rows]: rowKey };
+        colKeys.forEach((colKey, c) => {
+          const cell = cells[r * width + c];
+          out[colKey] = Number.isNaN(cell) ? null : cell;
+        });
+        return out;
+      });
+    } finally {
+      rowGroups?.free();
+      colGroups?.free();
+    }
+  }
+
+  // JavaScript fallback: sum, avg, count, min, max
+  const colKeys = [...new Set(data.map(item => String(item[columns])))];
+  return Object.entries(groupBy(data, rows)).map(([rowKey, items]) => {
+    const out: any = { [rows]: rowKey };
+    const byColumn = groupBy(items, columns);
+    colKeys.forEach(colKey => {
+      const cell = (byColumn[colKey] || []).map(item => Number(item[values]));
+      switch (aggregation) {
+        case 'sum':
+          out[colKey] = cell.reduce((a, b) => a + b, 0);
+          break;
+        case 'count':
+          out[colKey] = cell.length;
+          break;
+        case 'avg':
+          out[colKey] = cell.length ? cell.reduce((a, b) => a + b, 0) / cell.length : null;
+          break;
+        case 'min':
+          out[colKey] = cell.length ? Math.min(...cell) : null;
+          break;
+        case 'max':
+          out[colKey] = cell.length ? Math.max(...cell) : null;
+          break;
+        default:
+          throw new Error(`Aggregation '${aggregation}' requires WASM`);
+      }
+    });
+    return out;
+  });
 }
 
 function aggregate(data: any[], params: any): any {
   const { groupBy: groupKey, aggregations } = params;
 
+  const wasmResult = wasmAggregate(data, groupKey, aggregations);
+  if (wasmResult) {
+    return wasmResult;
+  }
+
   const grouped = groupBy(data, groupKey);
 
   return Object.entries(grouped).map(([key, items]) => {
@@ -270,4 +482,34 @@ function aggregate(data: any[], params: any): any {
   });
 }
 
+/**
+ * Group and aggregate in WASM; supports sum, avg, count, min, max, median,
+ * stddev and distinct. Returns null to use the JavaScript path.
+ */
+function wasmAggregate(data: any[], groupKey: string, aggregations: any[] = []): any[] | null {
+  if (!wasmModule?.GroupKeys) return null;
+  if (aggregations.some(agg => wasmAggregation(agg.operation) === undefined)) return null;
+
+  const grouping = wasmGrouping(data, groupKey);
+  try {
+    const results = grouping.stringKey(0).map((key: string) => ({ [groupKey]: key }));
+    // count keeps its JavaScript meaning: rows in the group, NaN or not
+    const sizes: Uint32Array = grouping.sizes();
+
+    aggregations.forEach(agg => {
+      const name = agg.name || `${agg.field}_${agg.operation}`;
+      const values: ArrayLike<number> = agg.operation === 'count'
+        ? sizes
+        : grouping.aggregate(numericColumn(data, agg.field), wasmAggregation(agg.operation));
+      results.forEach((row: any, g: number) => {
+        row[name] = values[g];
+      });
+    });
+
+    return results;
+  } finally {
+    grouping.free();
+  }
+}
+
 export { };
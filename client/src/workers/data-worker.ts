@@ -7,6 +7,8 @@
  * - Process data without blocking main thread
  */
 
+import { loadWasmModule } from '@/utils/wasm-loader';
+
 // Type definitions for messages
 interface WorkerMessage {
   action: 'fetch' | 'parse' | 'process';
@@ -34,7 +36,7 @@ self.onmessage = async (e: MessageEvent<WorkerMessage>) => {
         break;
         
       case 'parse':
-        result = await handleParse(data);
+        result = await handleParse(data, options);
         break;
         
       case 'process':
@@ -82,6 +84,9 @@ async function handleFetch(url: string, options?: Record<string, any>): Promise<
   
   if (contentType?.includes('application/json')) {
     return await response.json();
+  } else if (contentType?.includes('application/vnd.apache.arrow')) {
+    // Raw IPC bytes, ready for parse with options.format = 'arrow'
+    return await response.arrayBuffer();
   } else if (contentType?.includes('text/')) {
     return await response.text();
   } else {
@@ -89,10 +94,126 @@ async function handleFetch(url: string, options?: Record<string, any>): Promise<
   }
 }
 
+/**
+ * Columnar view of a WASM Table: numeric columns as Float64Array (NaN for
+ * missing values), booleans as Uint8Array, text as dictionary codes.
+ */
+function tableToColumns(wasm: any, table: any): any {
+  const columns = table.schema().map((column: any, i: number) => {
+    const validity: Uint8Array = table.validity(i);
+    switch (table.columnType(i)) {
+      case wasm.ColumnType.Text:
+        return { ...column, codes: table.codes(i), dictionary: table.dictionary(i), validity };
+      case wasm.ColumnType.Boolean:
+        return { ...column, values: table.booleans(i), validity };
+      default:
+        return { ...column, values: table.numbers(i), validity };
+    }
+  });
+  return { rowCount: table.rowCount, columns };
+}
+
+/**
+ * Result of a parse: columns by default, or Arrow IPC stream bytes when
+ * options.output is 'arrow' (for Arrow JS' tableFromIPC or DuckDB-wasm).
+ * Frees the table.
+ */
+function tableResult(wasm: any, table: any, options?: any): any {
+  try {
+    if (options?.output === 'arrow') {
+      return wasm.write_arrow(table, wasm.ArrowFormat.Stream);
+    }
+    return tableToColumns(wasm, table);
+  } finally {
+    table.free();
+  }
+}
+
+async function toBytes(data: any): Promise<Uint8Array> {
+  if (typeof data === 'string') return new TextEncoder().encode(data);
+  if (data instanceof Blob) return new Uint8Array(await data.arrayBuffer());
+  if (data instanceof ArrayBuffer) return new Uint8Array(data);
+  return new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+}
+
+/**
+ * Stream CSV bytes through the WASM CsvReader chunk by chunk, so the whole
+ * text never has to be held as a JS string.
+ * Accepts a string, ArrayBuffer, typed array, Blob/File or ReadableStream.
+ */
+async function wasmParseCsv(wasm: any, data: any, options?: any): Promise<any> {
+  const { delimiter = ',', header = true } = options || {};
+  const reader = new wasm.CsvReader(delimiter, header);
+  try {
+    if (typeof data === 'string') {
+      reader.push(new TextEncoder().encode(data));
+    } else if (data instanceof ArrayBuffer || ArrayBuffer.isView(data)) {
+      reader.push(data instanceof ArrayBuffer
+        ? new Uint8Array(data)
+        : new Uint8Array(data.buffer, data.byteOffset, data.byteLength));
+    } else {
+      const stream: ReadableStream<Uint8Array> = data instanceof Blob ? data.stream() : data;
+      const chunks = stream.getReader();
+      for (;;) {
+        const { done, value } = await chunks.read();
+        if (done) break;
+        reader.push(value);
+      }
+    }
+  } catch (error) {
+    reader.free();
+    throw error;
+  }
+  // finish() consumes the reader
+  return reader.finish();
+}
+
+/**
+ * Convert a JSON array of records straight to columns in WASM, skipping
+ * JSON.parse and the per-record object walk. Malformed input throws with
+ * the line and column of the problem.
+ */
+async function wasmParseJson(wasm: any, data: any): Promise<any> {
+  return wasm.parse_json(await toBytes(data));
+}
+
 /**
  * Parse large data sets
+ * options.format: 'csv' (default), 'json' or 'arrow' (IPC stream or file)
+ * options.output: 'rows' (default: an array of row objects, as before),
+ * 'columns' or 'arrow'. Columnar and Arrow output come from the WASM readers
+ * and throw when the module is unavailable, so the result shape never
+ * depends on the environment. Arrow input is always returned as columns
+ * unless output is 'arrow'.
  */
-async function handleParse(data: any): Promise<any> {
+async function handleParse(data: any, options?: any): Promise<any> {
+  const wasm = await loadWasmModule();
+  const columnar = options?.output === 'columns' || options?.output === 'arrow';
+  const isBytes = data instanceof ArrayBuffer || ArrayBuffer.isView(data);
+  const isStream = data instanceof Blob
+    || (typeof ReadableStream !== 'undefined' && data instanceof ReadableStream);
+  if (options?.format === 'arrow') {
+    if (!wasm?.read_arrow) {
+      throw new Error('Arrow parsing requires the WASM module');
+    }
+    return tableResult(wasm, wasm.read_arrow(await toBytes(data)), options);
+  }
+  if (options?.format === 'json') {
+    if (!columnar) {
+      return typeof data === 'string' ? JSON.parse(data) : data;
+    }
+    if (!wasm?.parse_json || !(typeof data === 'string' || isBytes || data instanceof Blob)) {
+      throw new Error('Columnar JSON output requires the WASM module and text, bytes or a Blob');
+    }
+    return tableResult(wasm, await wasmParseJson(wasm, data), options);
+  }
+  if (columnar) {
+    if (!wasm?.CsvReader || !(typeof data === 'string' || isBytes || isStream)) {
+      throw new Error('Columnar CSV output requires the WASM module and text, bytes or a stream');
+    }
+    return tableResult(wasm, await wasmParseCsv(wasm, data, options), options);
+  }
+
   // Example: Parse CSV data
   if (typeof data === 'string') {
     // Simple CSV parser (for demonstration)